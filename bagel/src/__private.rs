@@ -0,0 +1,207 @@
+//! Items used by the code generated by the derives. Do not rely on these
+
+/// Names the `T` in an `Option<T>`, even if the option is hidden behind a type alias
+pub trait OptionInner {
+    type Inner;
+}
+
+impl<T> OptionInner for Option<T> {
+    type Inner = T;
+}
+
+/// Implemented for the fields of a `#[repr(packed)]` struct that can have accessors. Since
+/// references to such fields may be unaligned, the accessors copy the values in and out instead
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not `Copy`, so it can't have accessors in a `#[repr(packed)]` struct",
+    label = "not `Copy`",
+    note = "skip the field with `#[bagel(skip)]`, or remove `#[repr(packed)]` from the struct"
+)]
+pub trait PackedField {}
+
+impl<T: Copy> PackedField for T {}
+
+/// Checks that a field of a `#[repr(packed)]` struct can have accessors, without a where clause
+pub const fn check_packed_field<T: PackedField + ?Sized>() {}
+
+/// Implemented for the fields that can have a `get_<field>_cloned` accessor. A `Clone` bound on a
+/// field type that doesn't mention a type parameter would be a hard error without a hint, so
+/// `Gtor` uses this trait instead
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not `Clone`, so it can't have a `get_<field>_cloned` accessor",
+    label = "not `Clone`",
+    note = "select the accessors for the field with `#[gtor(...)]` on it, or skip it with `#[gtor_skip]`"
+)]
+pub trait CloneField {
+    fn clone_field(&self) -> Self;
+}
+
+impl<T: Clone> CloneField for T {
+    fn clone_field(&self) -> Self {
+        self.clone()
+    }
+}
+
+/// Implemented for the fields that can have a `take_<field>` setter, like [`CloneField`]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not `Default`, so it can't have a `take_<field>` setter",
+    label = "not `Default`",
+    note = "select the setters for the field with `#[stor(...)]` on it, or skip it with `#[stor_skip]`"
+)]
+pub trait TakeField {
+    fn take_default() -> Self;
+}
+
+impl<T: Default> TakeField for T {
+    fn take_default() -> Self {
+        T::default()
+    }
+}
+
+/// Implemented for the fields that can have a constant `take_<field>` setter, like [`CloneField`]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not `Constdef`, so it can't have a constant `take_<field>` setter",
+    label = "not `Constdef`",
+    note = "select the setters for the field with `#[stor(...)]` on it, or skip it with `#[stor_skip]`"
+)]
+pub trait ConstTakeField {
+    const TAKE_DEFAULT: Self;
+}
+
+impl<T: crate::Constdef> ConstTakeField for T {
+    const TAKE_DEFAULT: Self = T::DEFAULT;
+}
+
+/// Fails to compile (in the generated code) if the type is not zeroable
+pub const fn assert_zeroable<T: crate::Zeroable + ?Sized>() {}
+
+/// A part of a string that is built by `const_concat!` or `const_format!`
+#[derive(Clone, Copy)]
+pub enum FmtPart<'a> {
+    Str(&'a str),
+    Int { negative: bool, abs: u128 },
+    Bool(bool),
+}
+
+/// Wraps an argument of `const_concat!` or `const_format!`, which is turned into a [`FmtPart`] by
+/// the `into_part` method of the type of the argument
+pub struct FmtArg<T>(pub T);
+
+impl<'a> FmtArg<&'a str> {
+    pub const fn into_part(self) -> FmtPart<'a> {
+        FmtPart::Str(self.0)
+    }
+}
+
+impl FmtArg<bool> {
+    pub const fn into_part(self) -> FmtPart<'static> {
+        FmtPart::Bool(self.0)
+    }
+}
+
+// an unsuffixed integer literal matches every integer type, so only `i32` takes `self` (which is
+// tried first) and the other integer types take `&self`. this makes integer literals fall back to
+// `i32`, like they do everywhere else
+impl FmtArg<i32> {
+    pub const fn into_part(self) -> FmtPart<'static> {
+        FmtPart::Int {
+            negative: self.0 < 0,
+            abs: self.0.unsigned_abs() as u128,
+        }
+    }
+}
+
+macro_rules! impl_fmt_arg {
+    (unsigned: $($unsigned:ty),*; signed: $($signed:ty),*) => {
+        $(
+            impl FmtArg<$unsigned> {
+                pub const fn into_part(&self) -> FmtPart<'static> {
+                    FmtPart::Int { negative: false, abs: self.0 as u128 }
+                }
+            }
+        )*
+        $(
+            impl FmtArg<$signed> {
+                pub const fn into_part(&self) -> FmtPart<'static> {
+                    FmtPart::Int { negative: self.0 < 0, abs: (self.0 as i128).unsigned_abs() }
+                }
+            }
+        )*
+    };
+}
+
+impl_fmt_arg!(unsigned: u8, u16, u32, u64, u128, usize; signed: i8, i16, i64, i128, isize);
+
+/// Returns the number of decimal digits in the integer
+const fn digits(mut int: u128) -> usize {
+    let mut digits = 1;
+    while int >= 10 {
+        int /= 10;
+        digits += 1;
+    }
+    digits
+}
+
+/// Returns the length of the string that the parts make up
+pub const fn formatted_len(parts: &[FmtPart]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        len += match parts[i] {
+            FmtPart::Str(s) => s.len(),
+            FmtPart::Int { negative, abs } => negative as usize + self::digits(abs),
+            FmtPart::Bool(true) => 4,
+            FmtPart::Bool(false) => 5,
+        };
+        i += 1;
+    }
+    len
+}
+
+/// Copies the bytes of `src` into `dst`, starting at `at`, and returns the index after them
+const fn write_bytes<const N: usize>(dst: &mut [u8; N], at: usize, src: &[u8]) -> usize {
+    let mut i = 0;
+    while i < src.len() {
+        dst[at + i] = src[i];
+        i += 1;
+    }
+    at + src.len()
+}
+
+/// Returns the bytes of the string that the parts make up, where `N` is [`formatted_len`]
+pub const fn format_bytes<const N: usize>(parts: &[FmtPart]) -> [u8; N] {
+    let mut bytes = [0; N];
+    let mut at = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        at = match parts[i] {
+            FmtPart::Str(s) => self::write_bytes(&mut bytes, at, s.as_bytes()),
+            FmtPart::Int { negative, mut abs } => {
+                if negative {
+                    bytes[at] = b'-';
+                    at += 1;
+                }
+                // the digits are written from the last one
+                let end = at + self::digits(abs);
+                let mut digit = end;
+                while digit > at {
+                    digit -= 1;
+                    bytes[digit] = b'0' + (abs % 10) as u8;
+                    abs /= 10;
+                }
+                end
+            }
+            FmtPart::Bool(true) => self::write_bytes(&mut bytes, at, b"true"),
+            FmtPart::Bool(false) => self::write_bytes(&mut bytes, at, b"false"),
+        };
+        i += 1;
+    }
+    bytes
+}
+
+/// Returns the string for the bytes built by [`format_bytes`], which are always valid UTF-8
+pub const fn formatted_str(bytes: &[u8]) -> &str {
+    match core::str::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(_) => panic!("the formatted string is not valid UTF-8"),
+    }
+}
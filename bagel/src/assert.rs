@@ -0,0 +1,191 @@
+//! # Compile-time assertions
+//!
+//! The macros in this module check a condition at compile-time, so that a broken assumption
+//! (like the size of a type that is written to disk, or a type that must be sent across threads)
+//! fails the build instead of surfacing at runtime. All of them can be used at module level and
+//! inside functions, but they can't refer to the generic parameters of an enclosing function.
+//!
+//! ```
+//! use bagel::assert::{assert_impl, assert_size, const_assert};
+//!
+//! const PAGE_SIZE: usize = 4096;
+//!
+//! #[repr(C)]
+//! struct Header {
+//!     magic: u32,
+//!     version: u32,
+//!     len: u64,
+//! }
+//!
+//! const_assert!(PAGE_SIZE.is_power_of_two());
+//! assert_size!(Header, 16);
+//! assert_impl!(Header: Send + Sync);
+//! ```
+//!
+
+pub use crate::{
+    assert_align, assert_eq_size, assert_impl, assert_not_impl, assert_size, const_assert,
+};
+
+#[macro_export]
+/// Asserts that a constant expression is true at compile-time, with an optional message
+///
+/// ## Example
+/// ```
+/// use bagel::const_assert;
+///
+/// const WORKERS: usize = 4;
+/// const_assert!(WORKERS > 0);
+/// const_assert!(WORKERS <= 64, "too many workers");
+/// ```
+/// A false condition fails the build, showing the condition (or the message):
+/// ```compile_fail
+/// use bagel::const_assert;
+///
+/// const WORKERS: usize = 0;
+/// const_assert!(WORKERS > 0);
+/// ```
+macro_rules! const_assert {
+    ($cond:expr $(,)?) => {
+        const _: () = ::core::assert!(
+            $cond,
+            "{}",
+            ::core::concat!("assertion failed: ", ::core::stringify!($cond))
+        );
+    };
+    ($cond:expr, $message:literal $(,)?) => {
+        const _: () = ::core::assert!($cond, "{}", $message);
+    };
+}
+
+#[macro_export]
+/// Asserts that the size of a type is exactly the given number of bytes
+///
+/// ## Example
+/// ```
+/// use bagel::assert_size;
+///
+/// assert_size!(u64, 8);
+/// assert_size!([u16; 4], 8);
+/// ```
+/// A different size fails the build, showing both sizes:
+/// ```compile_fail
+/// use bagel::assert_size;
+///
+/// assert_size!(u64, 4);
+/// ```
+macro_rules! assert_size {
+    ($ty:ty, $size:expr $(,)?) => {
+        // the compiler reports the expected size and the actual size if the lengths differ
+        const _: [(); $size] = [(); ::core::mem::size_of::<$ty>()];
+    };
+}
+
+#[macro_export]
+/// Asserts that the alignment of a type is exactly the given number of bytes
+///
+/// ## Example
+/// ```
+/// use bagel::assert_align;
+///
+/// #[repr(C, align(64))]
+/// struct CacheLine([u8; 64]);
+///
+/// assert_align!(CacheLine, 64);
+/// ```
+/// A different alignment fails the build, showing both alignments:
+/// ```compile_fail
+/// use bagel::assert_align;
+///
+/// assert_align!(u8, 2);
+/// ```
+macro_rules! assert_align {
+    ($ty:ty, $align:expr $(,)?) => {
+        const _: [(); $align] = [(); ::core::mem::align_of::<$ty>()];
+    };
+}
+
+#[macro_export]
+/// Asserts that two types have the same size
+///
+/// ## Example
+/// ```
+/// use bagel::assert_eq_size;
+///
+/// assert_eq_size!(u64, [u8; 8]);
+/// assert_eq_size!(Box<u8>, Option<Box<u8>>);
+/// ```
+/// Types of different sizes fail the build, showing both sizes:
+/// ```compile_fail
+/// use bagel::assert_eq_size;
+///
+/// assert_eq_size!(u32, u64);
+/// ```
+macro_rules! assert_eq_size {
+    ($left:ty, $right:ty $(,)?) => {
+        const _: [(); ::core::mem::size_of::<$left>()] = [(); ::core::mem::size_of::<$right>()];
+    };
+}
+
+#[macro_export]
+/// Asserts that a type implements all the given traits
+///
+/// ## Example
+/// ```
+/// use bagel::assert_impl;
+///
+/// assert_impl!(String: Send + Sync + Clone);
+/// assert_impl!(&'static str: Copy);
+/// ```
+/// A missing implementation fails the build, showing the trait that isn't implemented:
+/// ```compile_fail
+/// use bagel::assert_impl;
+/// use std::rc::Rc;
+///
+/// assert_impl!(Rc<u8>: Send);
+/// ```
+macro_rules! assert_impl {
+    ($ty:ty: $($bounds:tt)+) => {
+        const _: fn() = || {
+            fn assert_impl<T: ?::core::marker::Sized + $($bounds)+>() {}
+            assert_impl::<$ty>();
+        };
+    };
+}
+
+#[macro_export]
+/// Asserts that a type does **not** implement all the given traits (implementing only some of
+/// them is fine)
+///
+/// ## Example
+/// ```
+/// use bagel::assert_not_impl;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// assert_not_impl!(Rc<u8>: Send);
+/// assert_not_impl!(Cell<u8>: Send + Sync);
+/// ```
+/// Implementing every trait fails the build:
+/// ```compile_fail
+/// use bagel::assert_not_impl;
+///
+/// assert_not_impl!(u8: Send + Sync);
+/// ```
+macro_rules! assert_not_impl {
+    ($ty:ty: $($bounds:tt)+) => {
+        const _: fn() = || {
+            // if the type implements the traits, both impls apply and the call is ambiguous
+            trait TypeMustNotImplementTheseTraits<A> {
+                fn check() {}
+            }
+            impl<T: ?::core::marker::Sized> TypeMustNotImplementTheseTraits<()> for T {}
+            struct Implemented;
+            impl<T: ?::core::marker::Sized + $($bounds)+> TypeMustNotImplementTheseTraits<Implemented>
+                for T
+            {
+            }
+            let _ = <$ty as TypeMustNotImplementTheseTraits<_>>::check;
+        };
+    };
+}
@@ -1,9 +1,13 @@
 mod mem {
     use crate::Constdef;
+    use core::mem::ManuallyDrop;
     use core::mem::MaybeUninit;
     impl<T> Constdef for MaybeUninit<T> {
         const DEFAULT: Self = Self::uninit();
     }
+    impl<T: Constdef> Constdef for ManuallyDrop<T> {
+        const DEFAULT: Self = ManuallyDrop::new(T::DEFAULT);
+    }
 }
 
 mod cell {
@@ -22,6 +26,14 @@ mod cell {
     }
 }
 
+mod hash {
+    use crate::Constdef;
+    use core::hash::BuildHasherDefault;
+    impl<H> Constdef for BuildHasherDefault<H> {
+        const DEFAULT: Self = BuildHasherDefault::new();
+    }
+}
+
 mod marker {
     use crate::Constdef;
     use core::marker::PhantomData;
@@ -5,9 +5,27 @@ mod primitive;
 mod std {
     mod collections {
         use crate::Constdef;
-        use std::collections::LinkedList;
+        use std::collections::{HashMap, HashSet, LinkedList};
         impl<T> Constdef for LinkedList<T> {
             const DEFAULT: Self = LinkedList::new();
         }
+        impl<K, V, S: Constdef> Constdef for HashMap<K, V, S> {
+            const DEFAULT: Self = HashMap::with_hasher(S::DEFAULT);
+        }
+        impl<T, S: Constdef> Constdef for HashSet<T, S> {
+            const DEFAULT: Self = HashSet::with_hasher(S::DEFAULT);
+        }
+    }
+    mod sync {
+        // allow this because Constdef can also be used for statics
+        #![allow(clippy::declare_interior_mutable_const)]
+        use crate::Constdef;
+        use std::sync::{Mutex, RwLock};
+        impl<T: Constdef> Constdef for Mutex<T> {
+            const DEFAULT: Self = Mutex::new(T::DEFAULT);
+        }
+        impl<T: Constdef> Constdef for RwLock<T> {
+            const DEFAULT: Self = RwLock::new(T::DEFAULT);
+        }
     }
 }
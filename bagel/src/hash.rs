@@ -0,0 +1,282 @@
+//! # Hashing
+//!
+//! This module provides `const fn` implementations of common non-cryptographic hashes, so that
+//! hashes of strings and bytes (like the commands in a dispatch table, or protocol tags) can be
+//! computed at compile-time:
+//! - [`fnv1a_32`] and [`fnv1a_64`]: The [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/)
+//!   hash, which is very fast for short keys
+//! - [`xxh64`]: The 64-bit [xxHash](https://github.com/Cyan4973/xxHash), which is fast for keys of
+//!   any length
+//! - [`crc32`] and [`crc32c`]: The CRC-32 (IEEE) and CRC-32C (Castagnoli) checksums
+//!
+//! The same functions can be called at runtime, and always return the same results. The
+//! [`hash!`] macro computes a hash in the compiler itself, and expands to the
+//! resulting integer literal:
+//!
+//! ```
+//! use bagel::hash::{self, hash};
+//!
+//! const SET: u64 = hash::fnv1a_64(b"SET");
+//!
+//! fn dispatch(command: &str) -> &'static str {
+//!     match hash::fnv1a_64(command.as_bytes()) {
+//!         SET => "set",
+//!         hash!("GET") => "get",
+//!         _ => "unknown",
+//!     }
+//! }
+//!
+//! assert_eq!(dispatch("SET"), "set");
+//! assert_eq!(dispatch("GET"), "get");
+//! assert_eq!(hash!(crc32, "123456789"), 0xcbf43926);
+//! ```
+//!
+//! This module also provides [`FnvHasher`], a [`Hasher`] for the 64-bit FNV-1a hash that can be
+//! built at compile-time. Unlike the default hasher of [`HashMap`], which is seeded with random
+//! keys at runtime, an [`FnvBuildHasher`] is [`Constdef`], so maps and sets that use it can be
+//! declared in `static`s:
+//!
+//! ```
+//! use bagel::hash::FnvHashMap;
+//! use bagel::Constdef;
+//! use std::sync::Mutex;
+//!
+//! static USERS: Mutex<FnvHashMap<u64, String>> = Constdef::DEFAULT;
+//!
+//! USERS.lock().unwrap().insert(1, "sayan".to_owned());
+//! assert_eq!(USERS.lock().unwrap()[&1], "sayan");
+//! ```
+//!
+//! None of these hashes are resistant to HashDoS attacks, so avoid them for maps that are keyed
+//! by untrusted input.
+//!
+
+use crate::Constdef;
+use core::hash::{BuildHasherDefault, Hasher};
+use std::collections::{HashMap, HashSet};
+
+/// Expands to the hash of a string or byte string literal, computed by the compiler
+///
+/// The algorithm is given before the literal, and is one of `fnv1a_32`, `fnv1a_64` (the default),
+/// `xxh64` (with a seed of `0`), `crc32` and `crc32c`. The result is a `u32` or `u64` literal, so
+/// it can also be used in patterns.
+///
+/// ```
+/// use bagel::hash::{self, hash};
+///
+/// assert_eq!(hash!("SET"), hash::fnv1a_64(b"SET"));
+/// assert_eq!(hash!(xxh64, b"SET"), hash::xxh64(b"SET", 0));
+/// assert_eq!(hash!(crc32c, "SET"), hash::crc32c(b"SET"));
+/// ```
+pub use dough::hash;
+
+const FNV32_OFFSET_BASIS: u32 = 0x811c_9dc5;
+const FNV32_PRIME: u32 = 0x0100_0193;
+const FNV64_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV64_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Returns the 32-bit FNV-1a hash of the bytes
+pub const fn fnv1a_32(bytes: &[u8]) -> u32 {
+    let mut hash = FNV32_OFFSET_BASIS;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(FNV32_PRIME);
+        i += 1;
+    }
+    hash
+}
+
+/// Returns the 64-bit FNV-1a hash of the bytes
+pub const fn fnv1a_64(bytes: &[u8]) -> u64 {
+    self::fnv1a_64_update(FNV64_OFFSET_BASIS, bytes)
+}
+
+/// Continues a 64-bit FNV-1a hash with more bytes
+const fn fnv1a_64_update(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(FNV64_PRIME);
+        i += 1;
+    }
+    hash
+}
+
+const XXH_PRIME64_1: u64 = 0x9e37_79b1_85eb_ca87;
+const XXH_PRIME64_2: u64 = 0xc2b2_ae3d_27d4_eb4f;
+const XXH_PRIME64_3: u64 = 0x1656_67b1_9e37_79f9;
+const XXH_PRIME64_4: u64 = 0x85eb_ca77_c2b2_ae63;
+const XXH_PRIME64_5: u64 = 0x27d4_eb2f_1656_67c5;
+
+const fn read_u64(bytes: &[u8], i: usize) -> u64 {
+    u64::from_le_bytes([
+        bytes[i],
+        bytes[i + 1],
+        bytes[i + 2],
+        bytes[i + 3],
+        bytes[i + 4],
+        bytes[i + 5],
+        bytes[i + 6],
+        bytes[i + 7],
+    ])
+}
+
+const fn read_u32(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+}
+
+const fn xxh64_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(XXH_PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(XXH_PRIME64_1)
+}
+
+const fn xxh64_merge(acc: u64, value: u64) -> u64 {
+    (acc ^ self::xxh64_round(0, value))
+        .wrapping_mul(XXH_PRIME64_1)
+        .wrapping_add(XXH_PRIME64_4)
+}
+
+/// Returns the 64-bit xxHash (XXH64) of the bytes, with the given seed (use `0` for the hash that
+/// other implementations return by default)
+pub const fn xxh64(bytes: &[u8], seed: u64) -> u64 {
+    let len = bytes.len();
+    let mut i = 0;
+    let mut hash = if len >= 32 {
+        let mut v1 = seed.wrapping_add(XXH_PRIME64_1).wrapping_add(XXH_PRIME64_2);
+        let mut v2 = seed.wrapping_add(XXH_PRIME64_2);
+        let mut v3 = seed;
+        let mut v4 = seed.wrapping_sub(XXH_PRIME64_1);
+        while i + 32 <= len {
+            v1 = self::xxh64_round(v1, self::read_u64(bytes, i));
+            v2 = self::xxh64_round(v2, self::read_u64(bytes, i + 8));
+            v3 = self::xxh64_round(v3, self::read_u64(bytes, i + 16));
+            v4 = self::xxh64_round(v4, self::read_u64(bytes, i + 24));
+            i += 32;
+        }
+        let mut hash = v1
+            .rotate_left(1)
+            .wrapping_add(v2.rotate_left(7))
+            .wrapping_add(v3.rotate_left(12))
+            .wrapping_add(v4.rotate_left(18));
+        hash = self::xxh64_merge(hash, v1);
+        hash = self::xxh64_merge(hash, v2);
+        hash = self::xxh64_merge(hash, v3);
+        self::xxh64_merge(hash, v4)
+    } else {
+        seed.wrapping_add(XXH_PRIME64_5)
+    };
+    hash = hash.wrapping_add(len as u64);
+    while i + 8 <= len {
+        hash ^= self::xxh64_round(0, self::read_u64(bytes, i));
+        hash = hash
+            .rotate_left(27)
+            .wrapping_mul(XXH_PRIME64_1)
+            .wrapping_add(XXH_PRIME64_4);
+        i += 8;
+    }
+    if i + 4 <= len {
+        hash ^= (self::read_u32(bytes, i) as u64).wrapping_mul(XXH_PRIME64_1);
+        hash = hash
+            .rotate_left(23)
+            .wrapping_mul(XXH_PRIME64_2)
+            .wrapping_add(XXH_PRIME64_3);
+        i += 4;
+    }
+    while i < len {
+        hash ^= (bytes[i] as u64).wrapping_mul(XXH_PRIME64_5);
+        hash = hash.rotate_left(11).wrapping_mul(XXH_PRIME64_1);
+        i += 1;
+    }
+    // avalanche
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(XXH_PRIME64_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(XXH_PRIME64_3);
+    hash ^ (hash >> 32)
+}
+
+/// The reversed polynomial of CRC-32 (IEEE)
+const CRC32_POLY: u32 = 0xedb8_8320;
+/// The reversed polynomial of CRC-32C (Castagnoli)
+const CRC32C_POLY: u32 = 0x82f6_3b78;
+const CRC32_TABLE: [u32; 256] = self::crc32_table(CRC32_POLY);
+const CRC32C_TABLE: [u32; 256] = self::crc32_table(CRC32C_POLY);
+
+const fn crc32_table(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+const fn crc32_with(table: &[u32; 256], bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    let mut i = 0;
+    while i < bytes.len() {
+        crc = table[((crc ^ bytes[i] as u32) & 0xff) as usize] ^ (crc >> 8);
+        i += 1;
+    }
+    !crc
+}
+
+/// Returns the CRC-32 (IEEE) checksum of the bytes, as used by zlib, gzip and PNG
+pub const fn crc32(bytes: &[u8]) -> u32 {
+    self::crc32_with(&CRC32_TABLE, bytes)
+}
+
+/// Returns the CRC-32C (Castagnoli) checksum of the bytes, as used by iSCSI and ext4
+pub const fn crc32c(bytes: &[u8]) -> u32 {
+    self::crc32_with(&CRC32C_TABLE, bytes)
+}
+
+/// A [`Hasher`] for the 64-bit FNV-1a hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FnvHasher(u64);
+
+impl FnvHasher {
+    /// Returns a new hasher
+    pub const fn new() -> Self {
+        Self(FNV64_OFFSET_BASIS)
+    }
+}
+
+impl Constdef for FnvHasher {
+    const DEFAULT: Self = Self::new();
+}
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = self::fnv1a_64_update(self.0, bytes);
+    }
+}
+
+/// A [`BuildHasher`](core::hash::BuildHasher) for [`FnvHasher`]s, which is [`Constdef`]
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+/// A [`HashMap`] that uses the [`FnvHasher`], and can be created at compile-time
+pub type FnvHashMap<K, V> = HashMap<K, V, FnvBuildHasher>;
+/// A [`HashSet`] that uses the [`FnvHasher`], and can be created at compile-time
+pub type FnvHashSet<T> = HashSet<T, FnvBuildHasher>;
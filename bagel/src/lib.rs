@@ -9,13 +9,30 @@
 //! - [`Ctor`]: Derive constructors
 //! - [`Gtor`]: Derive getters
 //! - [`Stor`]: Derive setters
+//! - [`Zeroable`](derive.Zeroable.html): Derive the [`Zeroable`] marker for zero-initialisable types
+//! - [`Reset`]: Restore any [`Constdef`] value to its default
+//! - [`assert`](mod@assert): Check sizes, alignments, trait implementations and other conditions at
+//!   compile-time
+//! - [`map`]: Compile-time maps with a perfect hash function
+//! - [`layout`](macro@layout): Check the size, alignment and field offsets of a struct at
+//!   compile-time
+//! - [`hash`]: Compile-time hashes of strings and bytes, and a hasher that can be built at
+//!   compile-time, for maps and sets in `static`s
+//! - [`const_concat!`] and [`const_format!`]: Build `&'static str` constants from other `const`
+//!   strings, integers and `bool`s
 //! - [`def`]: Use the [default declaration syntax](https://github.com/skytable/bagel#default-declaration-syntax)
 //!
 
 #[macro_use]
 mod internal_macros;
+#[doc(hidden)]
+pub mod __private;
+pub mod assert;
 mod constdef_impls;
+pub mod hash;
 mod macros;
+pub mod map;
+mod zeroable_impls;
 
 /// # Constant defaults
 ///
@@ -44,6 +61,63 @@ pub trait Constdef {
     const DEFAULT: Self;
 }
 
+/// # Resetting values
+///
+/// The [`Reset`] trait restores a value to its default, which is useful for objects that are
+/// reused (like pooled connections). It is implemented for every [`Constdef`] type, so the default
+/// that a value is reset to is always [`Constdef::DEFAULT`].
+///
+/// To reset a single field instead, use `#[constdef(reset)]` to generate `reset_<field>` methods.
+/// See [`Constdef`](derive.Constdef.html#resetting-fields).
+///
+/// ```
+/// use bagel::{Constdef, Reset};
+///
+/// #[derive(Constdef)]
+/// struct Connection {
+///     requests: usize,
+///     buffer: Vec<u8>,
+/// }
+///
+/// let mut con = Connection { requests: 10, buffer: vec![1, 2, 3] };
+/// con.reset();
+/// assert_eq!(con.requests, 0);
+/// assert!(con.buffer.is_empty());
+/// ```
+///
+pub trait Reset {
+    /// Restores self to its default value
+    fn reset(&mut self);
+}
+
+impl<T: Constdef> Reset for T {
+    #[inline]
+    fn reset(&mut self) {
+        *self = Self::DEFAULT;
+    }
+}
+
+/// # Zero-initialisable types
+///
+/// The [`Zeroable`] trait marks types for which a value with every byte set to zero is valid (like
+/// `0`, `false`, or a `None` pointer). This trait is implemented for the primitives, the atomics,
+/// `Option<NonNull<T>>` and the `Option`s of the non-zero integers, as well as for arrays and tuples
+/// of zeroable types. Use `#[derive(bagel::Zeroable)]` to implement it for your structs.
+///
+/// `#[constdef(zeroed)]` uses this trait to build large default values (like huge arrays) in a
+/// single step, instead of having to evaluate every element at compile-time. See
+/// [`Constdef`](derive.Constdef.html#zero-initialised-defaults).
+///
+/// # Safety
+///
+/// A value of the type with every byte set to zero must be a valid value of the type
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not `Zeroable`, so it can't be zero-initialised",
+    label = "not `Zeroable`",
+    note = "use `#[derive(bagel::Zeroable)]` if every field of the type is zeroable"
+)]
+pub unsafe trait Zeroable {}
+
 // re-export macros
 
 /// # The `Constdef` macro
@@ -73,29 +147,142 @@ pub trait Constdef {
 ///
 /// const SPOOKY: SpookyFriend = SpookyFriend::default();
 ///
-/// #[test]
-/// fn test_struct_with_heap_fields() {
-///     // spooky name; it's empty!
-///     assert_eq!(SPOOKY.name, "");
-///     // spooky email; it's empty!
-///     assert_eq!(SPOOKY.email, "");
-///     // spooky friend has no friends!
-///     assert!(SPOOKY.friend_names.is_empty());
-///     // spooky userid; it's 0!
-///     assert_eq!(SPOOKY.userid, 0);
-///     // spooky tag; it's 0!
-///     assert_eq!(SPOOKY.mytag, 0);
-/// }
+/// // spooky name; it's empty!
+/// assert_eq!(SPOOKY.name, "");
+/// // spooky email; it's empty!
+/// assert_eq!(SPOOKY.email, "");
+/// // spooky friend has no friends!
+/// assert!(SPOOKY.friend_names.is_empty());
+/// // spooky userid; it's 0!
+/// assert_eq!(SPOOKY.userid, 0);
+/// // spooky tag; it's 0!
+/// assert_eq!(SPOOKY.my_tag, 0);
 /// ```
 /// Even more complex types are supported. See [crate level docs](crate) for more information.
 ///
+/// ## Options
+///
+/// By default, the macro generates an inherent `pub const fn default()`, an implementation of
+/// [`Default`] and an implementation of [`Constdef`]. If that conflicts with your own items, use
+/// the `#[constdef(...)]` attribute on the struct:
+/// - `fn_name = "..."`: Renames the inherent fn (for example, to `const_default`)
+/// - `vis = "..."`: Sets the visibility of the inherent fn (for example, `pub(crate)`, or `""` for a
+///   private fn)
+/// - `no_inherent`: Skips the inherent fn (use [`Constdef::DEFAULT`] instead)
+/// - `no_default_impl`: Skips the implementation of [`Default`]
+///
+/// ### Example
+///
+/// ```
+/// use bagel::Constdef;
+///
+/// #[derive(Constdef)]
+/// #[constdef(fn_name = "const_default", no_default_impl)]
+/// pub struct Limits {
+///     max_connections: usize,
+///     timeout: u64,
+/// }
+///
+/// impl Default for Limits {
+///     fn default() -> Self {
+///         Self { max_connections: 100, ..Self::const_default() }
+///     }
+/// }
+///
+/// const LIMITS: Limits = Limits::const_default();
+/// assert_eq!(LIMITS.max_connections, 0);
+/// assert_eq!(Limits::default().max_connections, 100);
+/// ```
+///
+/// ## Zero-initialised defaults
+///
+/// Evaluating the default for every element of a huge array (like a table with millions of slots)
+/// at compile-time is slow and uses a lot of memory. With `#[constdef(zeroed)]`, the default value
+/// has every byte set to zero instead, which is done in a single step. This needs every field to be
+/// [`Zeroable`], which is checked at compile-time. See [`Zeroable`](derive.Zeroable.html) for an
+/// example.
+///
+/// ## Assertions
+///
+/// `#[constdef(assert = "...")]` checks a condition on the default value at compile-time, so that
+/// a default that breaks an invariant of the type fails the build instead of surfacing at runtime.
+/// On the struct, `self` is the default value; on a field, `self` is the default value of that
+/// field. Assertions are not supported on generic structs.
+///
+/// ```
+/// use bagel::Constdef;
+///
+/// #[derive(Constdef)]
+/// #[constdef(assert = "self.min <= self.max")]
+/// struct Bounds {
+///     min: u8,
+///     max: u8,
+///     #[constdef(assert = "self.is_empty()")]
+///     name: String,
+/// }
+/// ```
+/// A failed assertion is reported at the condition that failed:
+/// ```compile_fail
+/// use bagel::Constdef;
+///
+/// #[derive(Constdef)]
+/// struct Pool {
+///     #[constdef(assert = "self > 0")]
+///     workers: usize,
+/// }
+/// ```
+///
+/// ## Unions
+///
+/// The macro can also be used on unions, in which case the default value initialises the first
+/// field of the union. Use `#[constdef(active)]` to initialise another field instead. Fields that
+/// need to be dropped must be wrapped in `ManuallyDrop`, which also implements [`Constdef`]:
+///
+/// ```
+/// use bagel::Constdef;
+///
+/// #[repr(C)]
+/// #[derive(Constdef, Clone, Copy)]
+/// union Cell {
+///     int: u64,
+///     #[constdef(active)]
+///     bytes: [u8; 8],
+/// }
+///
+/// const EMPTY: Cell = Cell::default();
+/// assert_eq!(unsafe { EMPTY.bytes }, [0; 8]);
+/// ```
+///
+/// ## Resetting fields
+///
+/// Every [`Constdef`] type implements [`Reset`](trait.Reset.html), which restores the whole value
+/// to its default. To restore a single field, add `#[constdef(reset)]` to the field (or to the
+/// struct, for every field) to generate a `reset_<field>` method. The other fields are left
+/// untouched, so nothing they own is dropped or reallocated.
+///
+/// ```
+/// use bagel::Constdef;
+///
+/// #[derive(Constdef)]
+/// struct Session {
+///     buffer: Vec<u8>,
+///     #[constdef(reset)]
+///     requests: usize,
+/// }
+///
+/// let mut session = Session { buffer: Vec::with_capacity(1024), requests: 10 };
+/// session.reset_requests();
+/// assert_eq!(session.requests, 0);
+/// assert_eq!(session.buffer.capacity(), 1024);
+/// ```
+///
 pub use dough::Constdef;
 
 /// # Ctor: Get a constructor derived
 ///
 /// The [`Ctor`] macro will take the fields in the order they are declared and generate a
-/// constructor, that is a `YourStruct::new()` function.
-///
+/// constructor, that is a `YourStruct::new()` function. The constructor's doc comment lists its
+/// arguments, along with the doc comments of the fields.
 ///
 /// ## Example
 /// ```
@@ -117,7 +304,11 @@ pub use dough::Constdef;
 /// The following attributes are available:
 /// - `#[ctor_const]`: Will make your ctors constant
 /// - `#[phantom]`: Will skip the specified [`PhantomData`](core::marker::PhantomData) field(s) in
-/// the constructor, automatically adding `PhantomData` in the requisite positions
+///   the constructor, automatically adding `PhantomData` in the requisite positions
+/// - `#[bagel(skip)]`: Will skip the specified field(s) in the constructor, initializing them with
+///   [`Default::default()`] (or with [`Constdef::DEFAULT`] for `#[ctor_const]` constructors)
+/// - `#[ctor(attrs(...))]`: Adds the given attributes to the constructor, instead of the default
+///   `#[inline]` and `#[must_use]` (for example, `#[ctor(attrs(inline(always)))]`)
 ///
 /// ## Constant constructors
 ///
@@ -163,17 +354,30 @@ pub use dough::Ctor;
 /// # Attributes
 ///
 /// The following attributes are available:
-/// - `#[gtor_const]`: Will make your gtors constant
+/// - `#[gtor_const]`: Will make your gtors (including `get_<field>_mut`) constant
 /// - `#[gtor_skip]`: Will skip generation of getters for specific fields
+/// - `#[bagel(skip)]`: Will skip generation of getters, setters and ctor arguments for specific fields
 /// - `#[gtor_copy]`: Makes the getter return a copy of the value, assuming that the type is [`Copy`]
 /// - `#[phantom]`: Marks the field as a [`PhantomData`](core::marker::PhantomData) field, hence
-///     skipping getters, setters and ctors for the field
+///   skipping getters, setters and ctors for the field
 /// - `#[gtor(...)]`: See [this example](#the-gtor-attribute)
 ///
 /// ## The `gtor` attribute
 ///
+/// The gtor attribute selects the kinds of accessors that are generated:
+/// - `get`: `get_<field>(&self)`, returning a reference (or a copy). This is the default if no
+///   selection is made
+/// - `get_mut`: `get_<field>_mut(&mut self)`, returning a mutable reference
+/// - `into`: `into_<field>(self)`, consuming the struct and returning the field
+/// - `clone`: `get_<field>_cloned(&self)`, returning a clone of the field. If the type of the field
+///   mentions a type parameter, the method is only available when the type is [`Clone`]. Any other
+///   field must be [`Clone`], so select other kinds on the fields that aren't
+/// - `inner`: accessors for the contents of lock and cell fields. See
+///   [this section](#lock-and-cell-fields)
+///
 /// Simply add the gtor attribute like this: `#[gtor(get, get_mut)]` on the top of your struct to
-/// get mutable and immutable reference methods to the fields in your struct.
+/// get mutable and immutable reference methods to the fields in your struct. A `#[gtor(...)]`
+/// attribute on a field overrides the selection for that field alone.
 ///
 /// ### Example
 ///
@@ -186,16 +390,172 @@ pub use dough::Ctor;
 ///     y_axis: u8,
 /// }
 ///
-/// #[test]
-/// fn test_get_and_get_mut() {
-///     let mut m = Mutable::new(0, 0);
-///     // move x by 1 unit
-///     *m.get_x_axis_mut() = 1;
-///     // move y by 2 units
-///     *m.get_y_axis_mut() = 2;
-///     assert_eq!(m.get_x_axis(), 1);
-///     assert_eq!(m.get_y_axis(), 2);
+/// let mut m = Mutable::new(0, 0);
+/// // move x by 1 unit
+/// *m.get_x_axis_mut() = 1;
+/// // move y by 2 units
+/// *m.get_y_axis_mut() = 2;
+/// assert_eq!(m.get_x_axis(), 1);
+/// assert_eq!(m.get_y_axis(), 2);
+/// ```
+///
+/// ### Per-field example
+///
+/// ```
+/// use bagel::Gtor;
+/// #[derive(Gtor)]
+/// pub struct Session {
+///     id: u64,
+///     #[gtor(get, get_mut)]
+///     buffer: Vec<u8>,
+/// }
+///
+/// let mut s = Session { id: 1, buffer: vec![] };
+/// s.get_buffer_mut().push(1);
+/// assert_eq!(s.get_buffer().len(), 1);
+/// assert_eq!(s.get_id(), 1);
+/// ```
+/// A field that isn't [`Clone`] fails the build when `clone` is selected for it:
+/// ```compile_fail
+/// use bagel::Gtor;
+/// pub struct Handle;
+///
+/// #[derive(Gtor)]
+/// #[gtor(clone)]
+/// pub struct Session {
+///     id: u64,
+///     // needs `#[gtor(get)]`
+///     handle: Handle,
+/// }
+/// ```
+///
+/// ## Lock and cell fields
+///
+/// For `Mutex`, `RwLock`, `Cell` and `RefCell` fields, the `inner` kind generates accessors for
+/// the contents instead of exposing the wrapper by reference. The types must be the ones from the
+/// standard library, named either directly (`Mutex<T>`) or through `std` or `core` (like
+/// `std::sync::Mutex<T>`). Types named through other paths (like `parking_lot::Mutex<T>`) are left
+/// alone:
+/// - `Mutex<T>`: `lock_<field>(&self)`
+/// - `RwLock<T>`: `read_<field>(&self)` and `write_<field>(&self)`
+/// - `Cell<T>`: `get_<field>(&self) -> T` and `set_<field>(&self, v)`. `get_<field>` is only
+///   generated if `T` is a primitive, a generic parameter or marked with `#[gtor_copy]`, and it
+///   replaces the usual getter. For other types, the usual getter (returning `&Cell<T>`) is kept
+/// - `RefCell<T>`: `borrow_<field>(&self)` and `borrow_<field>_mut(&self)`
+///
+/// Selecting `inner` on the struct only affects the lock and cell fields. The `Cell` setter takes
+/// `&self` and replaces the `set_<field>` of [`Stor`](derive.Stor.html), which isn't generated for
+/// such fields.
+///
+/// ### Example
+///
+/// ```
+/// use bagel::Gtor;
+/// use std::cell::{Cell, RefCell};
+/// use std::sync::Mutex;
+///
+/// #[derive(Gtor)]
+/// #[gtor(get, inner)]
+/// pub struct Session {
+///     id: u64,
+///     hits: Cell<u32>,
+///     log: RefCell<Vec<String>>,
+///     peers: Mutex<Vec<String>>,
+/// }
+///
+/// let s = Session {
+///     id: 1,
+///     hits: Cell::new(0),
+///     log: RefCell::new(vec![]),
+///     peers: Mutex::new(vec![]),
+/// };
+/// s.set_hits(s.get_hits() + 1);
+/// s.borrow_log_mut().push("connected".to_owned());
+/// s.lock_peers().unwrap().push("127.0.0.1".to_owned());
+/// assert_eq!(s.get_id(), 1);
+/// assert_eq!(s.get_hits(), 1);
+/// assert_eq!(s.borrow_log().len(), 1);
+/// ```
+///
+/// ## Atomic fields
+///
+/// For fields of the `core::sync::atomic` types (like `AtomicUsize`, `AtomicBool` or
+/// `AtomicPtr<T>`), the `get` kind generates `load_<field>` instead of `get_<field>`. It accepts the
+/// memory ordering as an argument, unless a default ordering is set with
+/// `#[gtor(ordering = "...")]` on the struct (a `Release` default loads with `Relaxed` and an
+/// `AcqRel` default loads with `Acquire`). As with lock and cell fields, the types must be named
+/// directly (`AtomicUsize`) or through `std` or `core` (like `std::sync::atomic::AtomicUsize`).
+///
+/// ### Example
+///
+/// ```
+/// use bagel::Gtor;
+/// use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+///
+/// #[derive(Gtor)]
+/// #[gtor(ordering = "Acquire")]
+/// pub struct State {
+///     running: AtomicBool,
+///     connections: AtomicUsize,
+/// }
+///
+/// let s = State { running: AtomicBool::new(true), connections: AtomicUsize::new(10) };
+/// assert!(s.load_running());
+/// assert_eq!(s.load_connections(), 10);
+/// s.connections.store(11, Ordering::Release);
+/// assert_eq!(s.load_connections(), 11);
+/// ```
+///
+/// ## Packed structs
+///
+/// References to the fields of a `#[repr(packed)]` struct may be unaligned, so for packed structs
+/// `get_<field>` returns a copy of the field instead. This needs every field with a getter to be
+/// [`Copy`] (skip the other fields). The `get_mut`, `clone` and `inner` kinds are not available,
+/// while `into` works as usual. [`Stor`](derive.Stor.html) writes the fields of packed structs by
+/// value in the same way. The generated code is safe, so it can be used with
+/// `#![forbid(unsafe_code)]`.
+///
+/// ### Example
+///
+/// ```
+/// use bagel::{Gtor, Stor};
+///
+/// #[derive(Gtor, Stor)]
+/// #[repr(C, packed)]
+/// #[stor(set, update)]
+/// pub struct Header {
+///     version: u8,
+///     len: u64,
+///     #[bagel(skip)]
+///     name: String,
 /// }
+///
+/// let mut h = Header { version: 1, len: 0, name: "log".to_owned() };
+/// h.set_len(128);
+/// h.update_len(|len| *len *= 2);
+/// assert_eq!(h.get_version(), 1);
+/// assert_eq!(h.get_len(), 256);
+/// ```
+///
+/// ## Selecting fields
+///
+/// Use `#[gtor(only(...))]` or `#[gtor(except(...))]` on the top of your struct to generate getters
+/// for only (or for all but) the listed fields. Fields marked with `#[gtor_skip]`, `#[phantom]` or
+/// `#[bagel(skip)]` are always skipped.
+///
+/// ### Example
+///
+/// ```
+/// use bagel::Gtor;
+/// #[derive(Gtor)]
+/// #[gtor(except(secret))]
+/// pub struct Account {
+///     name: &'static str,
+///     secret: &'static str,
+/// }
+///
+/// let a = Account { name: "sayan", secret: "hunter2" };
+/// assert_eq!(a.get_name(), "sayan");
 /// ```
 ///
 /// # Important notes
@@ -214,6 +574,78 @@ pub use dough::Ctor;
 /// Returns the value for the `<struct_field>` field in struct [`<struct_name>`]
 /// ```
 ///
+/// followed by the doc comments of the field itself. To replace the generated line, add a template
+/// with `#[gtor(doc = "...")]` on the struct, where `{field}`, `{struct}` and `{method}` are
+/// replaced with the names of the field, the struct and the generated method.
+/// [`Stor`](derive.Stor.html) accepts the same `#[stor(doc = "...")]` template for setters.
+///
+/// ### Example
+///
+/// ```
+/// use bagel::Gtor;
+///
+/// #[derive(Gtor)]
+/// #[gtor(doc = "Gets the {field} of this {struct}")]
+/// pub struct Session {
+///     /// The ID, which is unique for every server
+///     id: u64,
+/// }
+///
+/// // `get_id` is documented as:
+/// // Gets the id of this Session
+/// //
+/// // The ID, which is unique for every server
+/// let s = Session { id: 1 };
+/// assert_eq!(s.get_id(), 1);
+/// ```
+///
+/// ## Method attributes
+///
+/// Every accessor is `#[inline]`, and the ones that return a value are also `#[must_use]`. To use
+/// other attributes instead, list them with `#[gtor(attrs(...))]` on the struct or on a field (which
+/// overrides the struct's list for that field). [`Stor`](derive.Stor.html) does the same with
+/// `#[stor(attrs(...))]`, except that setters are only `#[inline]` by default.
+///
+/// ### Example
+///
+/// ```
+/// use bagel::Gtor;
+///
+/// #[derive(Gtor)]
+/// #[gtor(attrs(inline(always), must_use))]
+/// pub struct Packet {
+///     len: u32,
+///     #[gtor(attrs(cold))]
+///     trailer: Vec<u8>,
+/// }
+///
+/// let p = Packet { len: 0, trailer: vec![] };
+/// assert_eq!(p.get_len(), 0);
+/// assert!(p.get_trailer().is_empty());
+/// ```
+///
+/// Since getters are `#[must_use]` by default, ignoring their result is a warning:
+///
+/// ```compile_fail
+/// #![deny(unused_must_use)]
+/// use bagel::Gtor;
+///
+/// #[derive(Gtor)]
+/// pub struct Packet {
+///     len: u32,
+/// }
+///
+/// let p = Packet { len: 0 };
+/// p.get_len();
+/// ```
+///
+/// ## Field attributes
+///
+/// The `#[cfg(...)]`, `#[cfg_attr(...)]`, `#[deprecated]` and `#[doc(hidden)]` attributes of a
+/// field are carried over to its accessors (and [`Stor`](derive.Stor.html) does the same for its
+/// setters). [`Ctor`](derive.Ctor.html) and [`Constdef`](derive.Constdef.html) mirror the `cfg`
+/// attributes onto the constructor argument and the default value for the field.
+///
 pub use dough::Gtor;
 
 /// # Stor: Get the setters derived
@@ -242,11 +674,203 @@ pub use dough::Gtor;
 /// # Attributes
 ///
 /// The following attributes are available:
+/// - `#[stor_const]`: Will make your stors constant
 /// - `#[phantom]`: Skips the stor for the specified field(s), assuming they are
-/// [`PhantomData`](core::marker::PhantomData) fields. This has the same effect as `#[stor_skip]`
-/// but it makes it easier to use with the other macros, avoiding the need to write skips for phantom
-/// fields specifically
+///   [`PhantomData`](core::marker::PhantomData) fields. This has the same effect as `#[stor_skip]`
+///   but it makes it easier to use with the other macros, avoiding the need to write skips for phantom
+///   fields specifically
 /// - `#[stor_skip]`: Skips the stor for the specified field(s)
+/// - `#[bagel(skip)]`: Skips getters, setters and ctor arguments for the specified field(s)
+/// - `#[stor(only(...))]` and `#[stor(except(...))]`: Generates setters for only (or for all but) the
+///   listed fields
+/// - `#[stor(...)]`: See [this section](#the-stor-attribute)
+///
+/// ## The `stor` attribute
+///
+/// The stor attribute selects the kinds of setters that are generated:
+/// - `set`: `set_<field>(&mut self, v)`. This is the default if no selection is made. It is left out
+///   for `Cell` fields that get a `set_<field>(&self, v)` from
+///   [`#[gtor(inner)]`](derive.Gtor.html#lock-and-cell-fields)
+/// - `replace`: `replace_<field>(&mut self, v) -> T`, returning the previous value
+/// - `take`: `take_<field>(&mut self) -> T`, returning the value and leaving the [`Default`] value in
+///   its place (or the [`Constdef`] value for `#[stor_const]` structs). Like `clone` in
+///   [`Gtor`](derive.Gtor.html#the-gtor-attribute), a field whose type doesn't mention a type
+///   parameter must have a default value, so select other kinds on the fields that don't
+/// - `update`: `update_<field>(&mut self, |v| ...)`, mutating the field through a closure (and returning
+///   whatever the closure returns)
+/// - `collection`: helpers for collection fields. See [this section](#collection-fields)
+///
+/// Add it on the top of your struct to select setters for all fields, or on a field to override the
+/// selection for that field alone.
+///
+/// ### Example
+///
+/// ```
+/// use bagel::Stor;
+/// #[derive(Stor)]
+/// #[stor(set, replace)]
+/// pub struct Connection {
+///     buffer: Vec<u8>,
+///     #[stor(take, update)]
+///     handle: Option<u32>,
+/// }
+///
+/// let mut c = Connection { buffer: vec![], handle: Some(1) };
+/// let old = c.replace_buffer(vec![1, 2, 3]);
+/// assert!(old.is_empty());
+/// c.update_handle(|h| *h = h.map(|h| h + 1));
+/// assert_eq!(c.take_handle(), Some(2));
+/// assert_eq!(c.handle, None);
+/// ```
+/// A field without a [`Default`] value fails the build when `take` is selected for it:
+/// ```compile_fail
+/// use bagel::Stor;
+/// pub struct Handle(u32);
+///
+/// #[derive(Stor)]
+/// #[stor(set, take)]
+/// pub struct Connection {
+///     buffer: Vec<u8>,
+///     // needs `#[stor(set)]`
+///     handle: Handle,
+/// }
+/// ```
+///
+/// ## `Option` fields
+///
+/// For fields of type `Option<T>`, the `set` kind generates these methods instead of the plain setter:
+/// - `set_<field>(&mut self, v: T)`, setting the field to `Some(v)`
+/// - `clear_<field>(&mut self)`, setting the field to `None`
+/// - `take_<field>(&mut self) -> Option<T>`, taking the value and leaving `None` in its place
+/// - `get_or_insert_<field>_with(&mut self, || ...) -> &mut T`
+///
+/// Options are detected by their type, so if the `Option` is hidden behind a type alias, add
+/// `#[stor(option)]` to the field.
+///
+/// ### Example
+///
+/// ```
+/// use bagel::Stor;
+/// use std::time::Duration;
+///
+/// #[derive(Stor)]
+/// pub struct Session {
+///     timeout: Option<Duration>,
+/// }
+///
+/// let mut s = Session { timeout: None };
+/// s.set_timeout(Duration::from_secs(30));
+/// assert_eq!(s.take_timeout(), Some(Duration::from_secs(30)));
+/// assert_eq!(*s.get_or_insert_timeout_with(|| Duration::from_secs(10)), Duration::from_secs(10));
+/// s.clear_timeout();
+/// assert!(s.timeout.is_none());
+/// ```
+///
+/// ## Collection fields
+///
+/// For `Vec`, `VecDeque`, `HashMap`, `BTreeMap`, `HashSet`, `BTreeSet` and `String` fields, the
+/// `collection` kind generates helpers that mutate the collection without exposing its type. As
+/// with lock and cell fields in [`Gtor`](derive.Gtor.html#lock-and-cell-fields), the types must be
+/// named directly or through `std` or `alloc` (like `std::collections::HashMap<K, V>`), since
+/// other types with the same names (like `heapless::Vec<T, N>`) have different methods:
+/// - `push_<item>` (sequences and strings) or `insert_<item>` and `remove_<item>` (maps and sets)
+/// - `extend_<field>`, `clear_<field>` and `<field>_len`
+///
+/// `<item>` is derived from the field name with a simple rule: a trailing `ies` becomes `y`
+/// (`entries` becomes `entry`), and otherwise a single trailing `s` is removed, unless the name
+/// ends with `ss` (`items` becomes `item`, `class` stays `class`). Other names are used as they are.
+/// The rule doesn't know about irregular plurals or about singular words ending with `s`
+/// (`addresses` would become `addresse` and `status` would become `statu`), so use
+/// `#[stor(collection = "name")]` on such fields to choose the name yourself. Selecting
+/// `collection` on the struct only affects the collection fields.
+///
+/// ### Example
+///
+/// ```
+/// use bagel::Stor;
+/// use std::collections::HashMap;
+///
+/// #[derive(Stor)]
+/// pub struct Registry {
+///     #[stor(collection)]
+///     entries: HashMap<String, u64>,
+///     #[stor(collection = "peer")]
+///     peer_list: Vec<String>,
+/// }
+///
+/// let mut r = Registry { entries: HashMap::new(), peer_list: vec![] };
+/// r.insert_entry("uptime".to_owned(), 100);
+/// assert_eq!(r.remove_entry("uptime"), Some(100));
+/// r.push_peer("127.0.0.1".to_owned());
+/// assert_eq!(r.peer_list_len(), 1);
+/// ```
+///
+/// ## Packed structs
+///
+/// For `#[repr(packed)]` structs, the setters copy values in and out of the (possibly unaligned)
+/// fields, so every field with a setter must be [`Copy`]. The `collection` kind and
+/// `get_or_insert_<field>_with` are not available. See
+/// [`Gtor`](derive.Gtor.html#packed-structs) for an example.
+///
+/// ## Atomic fields
+///
+/// For fields of the `core::sync::atomic` types, the `set` kind generates `store_<field>`,
+/// `swap_<field>`, `compare_exchange_<field>` and (for integers) `fetch_add_<field>`, all of which
+/// take `&self`. They accept the memory ordering(s) as arguments, unless a default ordering is set
+/// with `#[stor(ordering = "...")]` on the struct. An `AcqRel` default stores with `Release` (and
+/// uses `Acquire` for the failure ordering of `compare_exchange_<field>`), while an `Acquire`
+/// default is rejected, since stores can't acquire. See [`Gtor`](derive.Gtor.html) for the
+/// matching `load_<field>` getter.
+///
+/// ### Example
+///
+/// ```
+/// use bagel::Stor;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// #[derive(Stor)]
+/// #[stor(ordering = "SeqCst")]
+/// pub struct Counters {
+///     hits: AtomicUsize,
+/// }
+///
+/// let c = Counters { hits: AtomicUsize::new(0) };
+/// c.store_hits(1);
+/// assert_eq!(c.fetch_add_hits(1), 1);
+/// assert_eq!(c.compare_exchange_hits(2, 10), Ok(2));
+/// assert_eq!(c.hits.load(Ordering::SeqCst), 10);
+/// ```
+///
+/// ## Constant setters
+///
+/// To make your setters `const`, add the `#[stor_const]` attribute to the top of your struct.
+/// Together with `#[gtor_const]` and [`Constdef`](derive.Constdef.html), this lets you build a
+/// default value and then tweak it in a constant context. Note that a `const` setter cannot drop
+/// the previous value, so fields with destructors (like [`String`]) cannot have `const` setters.
+///
+/// ### Example
+///
+/// ```
+/// use bagel::{Constdef, Gtor, Stor};
+///
+/// #[derive(Constdef, Gtor, Stor)]
+/// #[gtor_const]
+/// #[gtor(get, get_mut)]
+/// #[stor_const]
+/// pub struct Config {
+///     port: u16,
+///     workers: usize,
+/// }
+///
+/// const CONFIG: Config = {
+///     let mut config = Config::default();
+///     config.set_port(2003);
+///     *config.get_workers_mut() = 4;
+///     config
+/// };
+/// assert_eq!(CONFIG.get_port(), 2003);
+/// assert_eq!(CONFIG.get_workers(), 4);
+/// ```
 ///
 /// ## Doc-comments
 ///
@@ -256,3 +880,118 @@ pub use dough::Gtor;
 /// ```
 ///
 pub use dough::Stor;
+
+/// # Zeroable: Derive the zero-initialisation marker
+///
+/// The [`Zeroable`](derive.Zeroable.html) derive macro implements the [`Zeroable`] marker trait
+/// for a struct, after checking at compile-time that every field is zeroable. Type parameters
+/// must be zeroable as well.
+///
+/// ## Example
+///
+/// ```
+/// use bagel::{Constdef, Zeroable};
+///
+/// #[derive(Constdef, Zeroable)]
+/// pub struct Slot {
+///     requests: usize,
+///     active: bool,
+/// }
+///
+/// #[derive(Constdef)]
+/// #[constdef(zeroed)]
+/// pub struct SlotTable {
+///     slots: [Slot; 1 << 16],
+///     generation: u64,
+/// }
+///
+/// static TABLE: SlotTable = SlotTable::default();
+/// assert_eq!(TABLE.slots[1024].requests, 0);
+/// assert!(!TABLE.slots[65535].active);
+/// ```
+///
+/// Fields that aren't zeroable (like a [`String`], for which all zeroes would be a dangling
+/// pointer) are a compile-time error:
+///
+/// ```compile_fail
+/// use bagel::Zeroable;
+///
+/// #[derive(Zeroable)]
+/// pub struct Session {
+///     id: u64,
+///     user: String,
+/// }
+/// ```
+pub use dough::Zeroable;
+
+/// # The `layout` attribute
+///
+/// The [`layout`](macro@layout) attribute checks the layout of a struct at compile-time, so that
+/// structs that are written to disk or sent over the wire keep their exact size, alignment and
+/// field offsets across refactors:
+/// - `#[bagel::layout(size = 16, align = 8)]` on the struct checks its size and alignment (both
+///   are optional)
+/// - `#[layout(offset = 8)]` on a field checks its offset, using [`core::mem::offset_of`]
+///
+/// A mismatch fails the build, with an error that shows both the expected and the actual value.
+/// The attribute also generates an associated `const OFFSET_<FIELD>: usize` for every field (with
+/// the visibility of the field), for use in manual serialisation code. It is not supported on
+/// generic structs.
+///
+/// ## Example
+///
+/// ```
+/// #[bagel::layout(size = 16, align = 8)]
+/// #[repr(C)]
+/// pub struct Header {
+///     #[layout(offset = 0)]
+///     pub magic: u32,
+///     #[layout(offset = 4)]
+///     pub version: u16,
+///     #[layout(offset = 8)]
+///     pub len: u64,
+/// }
+///
+/// let mut buf = [0u8; 16];
+/// buf[Header::OFFSET_LEN..][..8].copy_from_slice(&1024u64.to_le_bytes());
+/// assert_eq!(Header::OFFSET_VERSION, 4);
+/// ```
+/// A wrong offset fails the build:
+/// ```compile_fail
+/// #[bagel::layout(size = 8)]
+/// #[repr(C)]
+/// pub struct Entry {
+///     tag: u8,
+///     #[layout(offset = 1)]
+///     value: u32,
+/// }
+/// ```
+pub use dough::layout;
+
+/// Formats `const` items into a `&'static str` at compile-time
+///
+/// This is a limited form of [`format!`] that works in constants: the format string only supports
+/// `{}` placeholders (and `{{` and `}}` for literal braces), and every argument is a constant
+/// expression of type `&str`, `bool` or a primitive integer type. It expands to
+/// [`const_concat!`].
+///
+/// ## Example
+/// ```
+/// use bagel::const_format;
+///
+/// const HOST: &str = "localhost";
+/// const PORT: u16 = 2003;
+/// const TLS: bool = false;
+/// const ADDR: &str = const_format!("{}:{} (tls: {})", HOST, PORT, TLS);
+///
+/// assert_eq!(ADDR, "localhost:2003 (tls: false)");
+/// ```
+/// The number of arguments must match the number of placeholders:
+/// ```compile_fail
+/// const ADDR: &str = bagel::const_format!("{}:{}", "localhost");
+/// ```
+/// And format specs are not supported:
+/// ```compile_fail
+/// const PORT: &str = bagel::const_format!("{:>5}", 2003);
+/// ```
+pub use dough::const_format;
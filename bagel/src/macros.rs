@@ -53,3 +53,35 @@ macro_rules! _defexpr {
         $expr
     };
 }
+
+#[macro_export]
+/// Concatenates `const` items (and literals) into a `&'static str` at compile-time
+///
+/// Unlike [`concat!`], which only accepts literals, the arguments can be any constant expression
+/// of type `&str`, `bool` or a primitive integer type (unsuffixed integer literals are `i32`s).
+/// See also [`const_format!`].
+///
+/// ## Example
+/// ```
+/// use bagel::const_concat;
+///
+/// const PREFIX: &str = "/api";
+/// const VERSION: u8 = 2;
+/// const USERS: &str = const_concat!(PREFIX, "/v", VERSION, "/users");
+///
+/// assert_eq!(USERS, "/api/v2/users");
+/// ```
+/// Other types, like floats, are not supported:
+/// ```compile_fail
+/// const PI: &str = bagel::const_concat!("pi is ", 3.14);
+/// ```
+macro_rules! const_concat {
+    ($($arg:expr),* $(,)?) => {{
+        const __BAGEL_PARTS: &[$crate::__private::FmtPart<'static>] =
+            &[$($crate::__private::FmtArg($arg).into_part()),*];
+        const __BAGEL_LEN: usize = $crate::__private::formatted_len(__BAGEL_PARTS);
+        const __BAGEL_BYTES: [u8; __BAGEL_LEN] = $crate::__private::format_bytes(__BAGEL_PARTS);
+        const __BAGEL_STR: &str = $crate::__private::formatted_str(&__BAGEL_BYTES);
+        __BAGEL_STR
+    }};
+}
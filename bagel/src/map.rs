@@ -0,0 +1,227 @@
+//! # Static maps
+//!
+//! The [`static_map!`] macro builds a [`StaticMap`] at compile-time, using a
+//! [perfect hash function](https://en.wikipedia.org/wiki/Perfect_hash_function) that is computed
+//! by the macro for the given keys. Looking up a key hashes it once and compares it against a
+//! single entry, and since [`StaticMap::get`] is a `const fn`, lookups also work at compile-time.
+//!
+//! Keys can be string literals (for a `StaticMap<&str, V>`), byte string literals (for a
+//! `StaticMap<&[u8], V>`) or integer literals (for a map keyed by any primitive integer type).
+//! Duplicate keys are rejected at compile-time.
+//!
+//! ```
+//! use bagel::map::{static_map, StaticMap};
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Action {
+//!     Get,
+//!     Set,
+//!     Del,
+//! }
+//!
+//! static KEYWORDS: StaticMap<&str, Action> = static_map! {
+//!     "GET" => Action::Get,
+//!     "SET" => Action::Set,
+//!     "DEL" => Action::Del,
+//! };
+//!
+//! assert_eq!(KEYWORDS.get("SET"), Some(&Action::Set));
+//! assert_eq!(KEYWORDS.get("set"), None);
+//! assert_eq!(KEYWORDS.len(), 3);
+//! ```
+//!
+//! ## Case-insensitive lookups
+//!
+//! With `#![ignore_ascii_case]` at the start of the macro, keys are compared ignoring the case of
+//! ASCII letters (like [`str::eq_ignore_ascii_case`]), which is useful for keywords. This is only
+//! supported for string and byte string keys.
+//!
+//! ```
+//! use bagel::map::{static_map, StaticMap};
+//!
+//! const PORTS: StaticMap<&[u8], u16> = static_map! {
+//!     #![ignore_ascii_case]
+//!     b"http" => 80,
+//!     b"https" => 443,
+//! };
+//!
+//! const HTTPS: Option<&u16> = PORTS.get(b"HTTPS");
+//! assert_eq!(HTTPS, Some(&443));
+//! ```
+//!
+
+/// Builds a [`StaticMap`] with a perfect hash function for the given keys
+///
+/// See the [module level docs](self) for more information.
+///
+/// ```
+/// use bagel::map::{static_map, StaticMap};
+///
+/// static ERRORS: StaticMap<u16, &str> = static_map! {
+///     404 => "not found",
+///     500 => "internal server error",
+/// };
+///
+/// assert_eq!(ERRORS.get(404), Some(&"not found"));
+/// assert!(!ERRORS.contains_key(200));
+/// ```
+/// Duplicate keys are reported at the duplicate:
+/// ```compile_fail
+/// use bagel::map::{static_map, StaticMap};
+///
+/// static ERRORS: StaticMap<u16, &str> = static_map! {
+///     404 => "not found",
+///     404 => "gone",
+/// };
+/// ```
+pub use dough::static_map;
+
+/// A map that is built at compile-time by [`static_map!`]
+///
+/// The entries are laid out in the order given by the perfect hash function, so
+/// [`StaticMap::entries`] doesn't return them in the order that they were written in.
+pub struct StaticMap<K: 'static, V: 'static> {
+    #[doc(hidden)]
+    pub __seed: u64,
+    #[doc(hidden)]
+    pub __ignore_ascii_case: bool,
+    #[doc(hidden)]
+    pub __displacements: &'static [(u32, u32)],
+    #[doc(hidden)]
+    pub __entries: &'static [(K, V)],
+}
+
+/// Returns the hash of the key, which must match the hash computed by `static_map!`
+const fn hash_bytes(bytes: &[u8], seed: u64, ignore_ascii_case: bool) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325 ^ seed;
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = if ignore_ascii_case {
+            bytes[i].to_ascii_lowercase()
+        } else {
+            bytes[i]
+        };
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    self::mix(hash)
+}
+
+/// The finalizer of SplitMix64, which spreads every bit of the input over the output
+const fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8], ignore_ascii_case: bool) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        let equal = if ignore_ascii_case {
+            a[i].eq_ignore_ascii_case(&b[i])
+        } else {
+            a[i] == b[i]
+        };
+        if !equal {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+impl<K, V> StaticMap<K, V> {
+    /// Returns the number of entries in the map
+    pub const fn len(&self) -> usize {
+        self.__entries.len()
+    }
+    /// Returns true if the map has no entries
+    pub const fn is_empty(&self) -> bool {
+        self.__entries.is_empty()
+    }
+    /// Returns all the entries of the map
+    pub const fn entries(&self) -> &'static [(K, V)] {
+        self.__entries
+    }
+    /// Returns the only slot that the key with the given hash can be in
+    const fn slot(&self, hash: u64) -> Option<usize> {
+        if self.__entries.is_empty() {
+            return None;
+        }
+        let (g, f1, f2) = ((hash >> 32) as u32, hash as u32, self::mix(hash) as u32);
+        let (d1, d2) = self.__displacements[(g % self.__displacements.len() as u32) as usize];
+        let slot = d2.wrapping_add(f1.wrapping_mul(d1)).wrapping_add(f2);
+        Some((slot % self.__entries.len() as u32) as usize)
+    }
+}
+
+impl<V> StaticMap<&'static str, V> {
+    /// Returns the value for the key, if it is in the map
+    pub const fn get(&self, key: &str) -> Option<&V> {
+        let hash = self::hash_bytes(key.as_bytes(), self.__seed, self.__ignore_ascii_case);
+        match self.slot(hash) {
+            Some(slot)
+                if self::bytes_eq(
+                    self.__entries[slot].0.as_bytes(),
+                    key.as_bytes(),
+                    self.__ignore_ascii_case,
+                ) =>
+            {
+                Some(&self.__entries[slot].1)
+            }
+            _ => None,
+        }
+    }
+    /// Returns true if the key is in the map
+    pub const fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<V> StaticMap<&'static [u8], V> {
+    /// Returns the value for the key, if it is in the map
+    pub const fn get(&self, key: &[u8]) -> Option<&V> {
+        let hash = self::hash_bytes(key, self.__seed, self.__ignore_ascii_case);
+        match self.slot(hash) {
+            Some(slot) if self::bytes_eq(self.__entries[slot].0, key, self.__ignore_ascii_case) => {
+                Some(&self.__entries[slot].1)
+            }
+            _ => None,
+        }
+    }
+    /// Returns true if the key is in the map
+    pub const fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+macro_rules! impl_integer_keys {
+    ($($ty:ty),*) => {
+        $(
+            impl<V> StaticMap<$ty, V> {
+                /// Returns the value for the key, if it is in the map
+                pub const fn get(&self, key: $ty) -> Option<&V> {
+                    // every integer is hashed as its sign-extended 128-bit value, so that the
+                    // macro doesn't need to know the type of the keys
+                    let bytes = (key as i128 as u128).to_le_bytes();
+                    match self.slot(self::hash_bytes(&bytes, self.__seed, false)) {
+                        Some(slot) if self.__entries[slot].0 == key => {
+                            Some(&self.__entries[slot].1)
+                        }
+                        _ => None,
+                    }
+                }
+                /// Returns true if the key is in the map
+                pub const fn contains_key(&self, key: $ty) -> bool {
+                    self.get(key).is_some()
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_keys!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
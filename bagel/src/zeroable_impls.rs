@@ -0,0 +1,60 @@
+use crate::Zeroable;
+use core::cell::{Cell, UnsafeCell};
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+use core::ptr::NonNull;
+use core::sync::atomic::{
+    AtomicBool, AtomicI16, AtomicI32, AtomicI64, AtomicI8, AtomicIsize, AtomicPtr, AtomicU16,
+    AtomicU32, AtomicU64, AtomicU8, AtomicUsize,
+};
+
+macro_rules! impl_zeroable {
+    ($($ty:ty),*) => {
+        $(unsafe impl Zeroable for $ty {})*
+    };
+}
+
+// all zeroes is zero (or false, or '\0') for the primitives and for the atomics
+impl_zeroable! {
+    u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64, bool, char, (),
+    AtomicBool, AtomicU8, AtomicI8, AtomicU16, AtomicI16, AtomicU32, AtomicI32, AtomicU64,
+    AtomicI64, AtomicUsize, AtomicIsize
+}
+
+// a null pointer is `None` for the non-null types
+impl_zeroable! {
+    Option<NonZeroU8>, Option<NonZeroI8>, Option<NonZeroU16>, Option<NonZeroI16>,
+    Option<NonZeroU32>, Option<NonZeroI32>, Option<NonZeroU64>, Option<NonZeroI64>,
+    Option<NonZeroU128>, Option<NonZeroI128>, Option<NonZeroUsize>, Option<NonZeroIsize>
+}
+
+unsafe impl<T> Zeroable for Option<NonNull<T>> {}
+unsafe impl<T> Zeroable for AtomicPtr<T> {}
+unsafe impl<T: ?Sized> Zeroable for PhantomData<T> {}
+unsafe impl<T> Zeroable for MaybeUninit<T> {}
+unsafe impl<T> Zeroable for *const T {}
+unsafe impl<T> Zeroable for *mut T {}
+unsafe impl<T: Zeroable> Zeroable for Cell<T> {}
+unsafe impl<T: Zeroable> Zeroable for UnsafeCell<T> {}
+unsafe impl<T: Zeroable, const N: usize> Zeroable for [T; N] {}
+
+macro_rules! impl_zeroable_tuple {
+    ($(($($elem:ident),*)),*) => {
+        $(unsafe impl<$($elem: Zeroable),*> Zeroable for ($($elem,)*) {})*
+    };
+}
+
+impl_zeroable_tuple! {
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H)
+}
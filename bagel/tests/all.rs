@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison)]
+
 use bagel::Constdef;
 
 #[derive(Constdef)]
@@ -20,3 +22,1692 @@ fn nested_object() {
     assert_eq!(PORT_LOGGER.ports[65535].admin, false);
     assert_eq!(PORT_LOGGER.root_pid, 0);
 }
+
+#[derive(Constdef)]
+struct Grid {
+    cells: [[u8; 3]; 2],
+    spans: ((u8, bool), [(u16, char); 2]),
+}
+
+const GRID: Grid = Grid::default();
+
+#[test]
+fn nested_arrays_and_tuples() {
+    assert_eq!(GRID.cells, [[0; 3]; 2]);
+    assert_eq!(GRID.spans, ((0, false), [(0, '\0'); 2]));
+}
+
+mod skip {
+    use bagel::{Ctor, Gtor, Stor};
+    use core::marker::PhantomData;
+
+    /// Inherent methods shadow trait methods, so these are only called if the derive did *not*
+    /// generate the accessor
+    trait NotGenerated {
+        fn get_gtor_skipped(&self) -> &'static str {
+            "skipped"
+        }
+        fn get_marker(&self) -> &'static str {
+            "skipped"
+        }
+        fn set_marker(&mut self, _: ()) -> &'static str {
+            "skipped"
+        }
+        fn get_hidden(&self) -> &'static str {
+            "skipped"
+        }
+        fn set_hidden(&mut self, _: ()) -> &'static str {
+            "skipped"
+        }
+        fn set_stor_skipped(&mut self, _: ()) -> &'static str {
+            "skipped"
+        }
+        fn get_b(&self) -> &'static str {
+            "skipped"
+        }
+        fn set_a(&mut self, _: ()) -> &'static str {
+            "skipped"
+        }
+    }
+    impl<T> NotGenerated for T {}
+
+    #[derive(Ctor, Gtor, Stor)]
+    struct Skips {
+        #[gtor_skip]
+        gtor_skipped: u8,
+        #[stor_skip]
+        stor_skipped: u8,
+        #[phantom]
+        marker: PhantomData<u8>,
+        #[bagel(skip)]
+        hidden: u8,
+        visible: u8,
+    }
+
+    #[test]
+    fn field_level_skips() {
+        let mut s = Skips::new(1, 2, 3);
+        // gtor_skip only skips the getter
+        assert_eq!(s.get_gtor_skipped(), "skipped");
+        s.set_gtor_skipped(10);
+        assert_eq!(s.gtor_skipped, 10);
+        // stor_skip only skips the setter
+        assert_eq!(s.set_stor_skipped(()), "skipped");
+        assert_eq!(s.get_stor_skipped(), 2);
+        // phantom skips both
+        assert_eq!(s.get_marker(), "skipped");
+        assert_eq!(s.set_marker(()), "skipped");
+        // bagel(skip) skips both, and the ctor uses the default value
+        assert_eq!(s.get_hidden(), "skipped");
+        assert_eq!(s.set_hidden(()), "skipped");
+        assert_eq!(s.hidden, 0);
+        // the rest are untouched
+        s.set_visible(4);
+        assert_eq!(s.get_visible(), 4);
+        let _ = s.marker;
+    }
+
+    #[derive(Gtor, Stor)]
+    #[gtor(only(a, c))]
+    #[stor(except(a))]
+    struct Lists {
+        a: u8,
+        b: u8,
+        c: u8,
+    }
+
+    #[test]
+    fn container_level_lists() {
+        let mut l = Lists { a: 1, b: 2, c: 3 };
+        assert_eq!(l.get_a(), 1);
+        assert_eq!(l.get_b(), "skipped");
+        assert_eq!(l.get_c(), 3);
+        assert_eq!(l.set_a(()), "skipped");
+        l.set_b(20);
+        l.set_c(30);
+        assert_eq!(l.b, 20);
+        assert_eq!(l.get_c(), 30);
+    }
+
+    #[derive(Gtor)]
+    #[gtor(only(a, b), except(b))]
+    struct OnlyExcept {
+        a: u8,
+        b: u8,
+        #[gtor_skip]
+        c: u8,
+    }
+
+    trait NotGeneratedOnlyExcept {
+        fn get_c(&self) -> &'static str {
+            "skipped"
+        }
+    }
+    impl<T> NotGeneratedOnlyExcept for T {}
+
+    #[test]
+    fn only_and_except() {
+        let oe = OnlyExcept { a: 1, b: 2, c: 3 };
+        assert_eq!(oe.get_a(), 1);
+        assert_eq!(oe.get_b(), "skipped");
+        assert_eq!(oe.get_c(), "skipped");
+        assert_eq!(oe.b + oe.c, 5);
+    }
+
+    #[derive(Ctor)]
+    #[ctor_const]
+    struct ConstSkip {
+        a: u8,
+        #[bagel(skip)]
+        b: u8,
+    }
+
+    const CONST_SKIP: ConstSkip = ConstSkip::new(1);
+
+    #[test]
+    fn const_ctor_skip() {
+        assert_eq!(CONST_SKIP.a, 1);
+        assert_eq!(CONST_SKIP.b, 0);
+    }
+}
+
+mod kinds {
+    use bagel::Gtor;
+
+    /// Inherent methods shadow trait methods, so these are only called if the derive did *not*
+    /// generate the accessor
+    trait NotGenerated {
+        fn get_id(&self) -> &'static str {
+            "skipped"
+        }
+        fn get_buffer(&self) -> &'static str {
+            "skipped"
+        }
+        fn get_id_mut(&mut self) -> &'static str {
+            "skipped"
+        }
+        fn get_name_mut(&mut self) -> &'static str {
+            "skipped"
+        }
+        fn get_buffer_cloned(&self) -> &'static str {
+            "skipped"
+        }
+    }
+    impl<T> NotGenerated for T {}
+
+    #[derive(Gtor)]
+    #[gtor(clone)]
+    struct Connection {
+        #[gtor(get, into)]
+        name: String,
+        #[gtor(get_mut)]
+        buffer: Vec<u8>,
+        id: u64,
+    }
+
+    #[test]
+    fn container_and_field_selection() {
+        let mut c = Connection {
+            name: "conn".to_owned(),
+            buffer: vec![1, 2],
+            id: 1,
+        };
+        // the struct level selection removes the default getter
+        assert_eq!(c.get_id(), "skipped");
+        assert_eq!(c.get_id_cloned(), 1);
+        assert_eq!(c.get_id_mut(), "skipped");
+        // field selections override the struct selection
+        assert_eq!(c.get_buffer(), "skipped");
+        assert_eq!(c.get_buffer_cloned(), "skipped");
+        c.get_buffer_mut().push(3);
+        assert_eq!(c.get_name_mut(), "skipped");
+        assert_eq!(c.get_name(), "conn");
+        assert_eq!(c.buffer, [1, 2, 3]);
+        assert_eq!(c.into_name(), "conn");
+    }
+
+    #[derive(Gtor)]
+    struct Generic<T> {
+        #[gtor(clone)]
+        inner: T,
+    }
+
+    struct NotClone;
+
+    #[test]
+    fn clone_on_generic_field() {
+        let g = Generic { inner: 10u8 };
+        assert_eq!(g.get_inner_cloned(), 10);
+        // `get_inner_cloned` is only available when the field is `Clone`
+        let g = Generic { inner: NotClone };
+        let NotClone = g.inner;
+    }
+
+    #[derive(Gtor)]
+    #[gtor(clone)]
+    struct Mixed<T> {
+        ids: Vec<T>,
+        name: String,
+        #[gtor(get)]
+        handle: NotClone,
+    }
+
+    #[test]
+    fn clone_with_fields_that_are_not_clone() {
+        let m = Mixed {
+            ids: vec![1u8, 2],
+            name: "mixed".to_owned(),
+            handle: NotClone,
+        };
+        assert_eq!(m.get_ids_cloned(), [1, 2]);
+        assert_eq!(m.get_name_cloned(), "mixed");
+        let NotClone = m.get_handle();
+        // `get_ids_cloned` is only available when the elements are `Clone`
+        let m = Mixed {
+            ids: vec![NotClone],
+            name: String::new(),
+            handle: NotClone,
+        };
+        assert_eq!(m.ids.len(), 1);
+    }
+}
+
+mod const_accessors {
+    use bagel::{Constdef, Gtor, Stor};
+
+    #[derive(Constdef, Gtor, Stor)]
+    #[gtor_const]
+    #[gtor(get, get_mut)]
+    #[stor_const]
+    struct Config {
+        port: u16,
+        workers: usize,
+        limits: (u32, u32),
+    }
+
+    const CONFIG: Config = {
+        let mut config = Config::default();
+        config.set_port(2003);
+        *config.get_workers_mut() = 4;
+        config.get_limits_mut().1 = 128;
+        config
+    };
+
+    #[test]
+    fn tweak_default_in_const() {
+        assert_eq!(CONFIG.get_port(), 2003);
+        assert_eq!(CONFIG.get_workers(), 4);
+        assert_eq!(*CONFIG.get_limits(), (0, 128));
+    }
+}
+
+mod setter_kinds {
+    use bagel::{Constdef, Stor};
+
+    /// Inherent methods shadow trait methods, so these are only called if the derive did *not*
+    /// generate the accessor
+    trait NotGenerated {
+        fn set_handle(&mut self, _: ()) -> &'static str {
+            "skipped"
+        }
+        fn take_buffer(&mut self) -> &'static str {
+            "skipped"
+        }
+    }
+    impl<T> NotGenerated for T {}
+
+    #[derive(Stor)]
+    #[stor(set, replace)]
+    struct ConnectionState {
+        buffer: Vec<u8>,
+        #[stor(take, update)]
+        handle: Option<u32>,
+        #[stor(update)]
+        requests: usize,
+    }
+
+    #[test]
+    fn selected_setters() {
+        let mut cs = ConnectionState {
+            buffer: vec![1],
+            handle: Some(1),
+            requests: 0,
+        };
+        cs.set_buffer(vec![2]);
+        assert_eq!(cs.replace_buffer(vec![3]), [2]);
+        assert_eq!(cs.buffer, [3]);
+        assert_eq!(cs.take_buffer(), "skipped");
+        assert_eq!(cs.set_handle(()), "skipped");
+        assert_eq!(cs.update_handle(|h| h.replace(2)), Some(1));
+        assert_eq!(cs.take_handle(), Some(2));
+        assert_eq!(cs.handle, None);
+        cs.update_requests(|r| *r += 1);
+        assert_eq!(cs.requests, 1);
+    }
+
+    #[derive(Constdef, Stor)]
+    #[stor_const]
+    #[stor(set, replace, take)]
+    struct Counters {
+        hits: u64,
+        misses: u64,
+    }
+
+    const COUNTERS: (Counters, u64, u64) = {
+        let mut counters = Counters::default();
+        counters.set_hits(10);
+        let old_misses = counters.replace_misses(5);
+        let old_hits = counters.take_hits();
+        (counters, old_hits, old_misses)
+    };
+
+    #[test]
+    fn const_setter_kinds() {
+        let (counters, old_hits, old_misses) = COUNTERS;
+        assert_eq!(old_hits, 10);
+        assert_eq!(old_misses, 0);
+        assert_eq!(counters.hits, 0);
+        assert_eq!(counters.misses, 5);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct NoDefault(u8);
+
+    #[derive(Stor)]
+    #[stor(take)]
+    struct Session<T> {
+        value: T,
+        name: String,
+        #[stor(set)]
+        handle: NoDefault,
+    }
+
+    #[test]
+    fn take_with_fields_without_defaults() {
+        let mut s = Session {
+            value: 1u8,
+            name: "session".to_owned(),
+            handle: NoDefault(1),
+        };
+        assert_eq!(s.take_value(), 1);
+        assert_eq!(s.take_name(), "session");
+        s.set_handle(NoDefault(2));
+        assert_eq!((s.value, s.name.as_str(), s.handle), (0, "", NoDefault(2)));
+        // `take_value` is only available when the value has a default
+        let s = Session {
+            value: NoDefault(0),
+            name: String::new(),
+            handle: NoDefault(0),
+        };
+        assert_eq!(s.value, NoDefault(0));
+    }
+
+    #[derive(Stor)]
+    #[stor(set, take)]
+    #[repr(C, packed)]
+    struct Packed {
+        len: u32,
+        #[stor(set)]
+        tag: Tag,
+    }
+
+    #[derive(Clone, Copy)]
+    struct Tag(u8);
+
+    #[test]
+    fn packed_take() {
+        let mut p = Packed {
+            len: 0,
+            tag: Tag(1),
+        };
+        p.set_len(10);
+        let len = p.take_len();
+        p.set_tag(Tag(2));
+        assert_eq!((len, { p.len }, { p.tag }.0), (10, 0, 2));
+    }
+}
+
+mod option_setters {
+    use bagel::{Constdef, Stor};
+    use std::time::Duration;
+
+    type Handle = Option<u32>;
+
+    #[derive(Stor)]
+    struct Session {
+        timeout: Option<Duration>,
+        peer: core::option::Option<String>,
+        #[stor(option)]
+        handle: Handle,
+        #[stor(replace)]
+        token: Option<u64>,
+    }
+
+    #[test]
+    fn option_helpers() {
+        let mut s = Session {
+            timeout: None,
+            peer: None,
+            handle: None,
+            token: Some(1),
+        };
+        s.set_timeout(Duration::from_secs(1));
+        assert_eq!(s.timeout, Some(Duration::from_secs(1)));
+        s.clear_timeout();
+        assert_eq!(s.timeout, None);
+        assert_eq!(s.get_or_insert_peer_with(|| "peer".to_owned()), "peer");
+        s.get_or_insert_peer_with(|| unreachable!()).push('1');
+        assert_eq!(s.take_peer().as_deref(), Some("peer1"));
+        assert_eq!(s.peer, None);
+        s.set_handle(10);
+        assert_eq!(s.take_handle(), Some(10));
+        assert_eq!(s.handle, None);
+        // only the selected kinds are generated
+        assert_eq!(s.replace_token(None), Some(1));
+    }
+
+    #[derive(Constdef, Stor)]
+    #[stor_const]
+    struct Limits {
+        max_connections: Option<usize>,
+    }
+
+    const LIMITS: Limits = {
+        let mut limits = Limits::default();
+        limits.set_max_connections(1024);
+        limits
+    };
+
+    #[test]
+    fn const_option_setters() {
+        assert_eq!(LIMITS.max_connections, Some(1024));
+        let mut limits = LIMITS;
+        assert_eq!(limits.take_max_connections(), Some(1024));
+        limits.clear_max_connections();
+        assert_eq!(limits.max_connections, None);
+    }
+}
+
+mod collections {
+    use bagel::Stor;
+    use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+    /// Inherent methods shadow trait methods, so these are only called if the derive did *not*
+    /// generate the accessor
+    trait NotGenerated {
+        fn set_items(&mut self, _: ()) -> &'static str {
+            "skipped"
+        }
+    }
+    impl<T> NotGenerated for T {}
+
+    #[derive(Stor)]
+    struct Registry {
+        id: u64,
+        #[stor(collection)]
+        items: Vec<u32>,
+        #[stor(collection)]
+        entries: HashMap<String, u32>,
+        #[stor(collection = "peer")]
+        peer_list: HashSet<String>,
+        #[stor(collection)]
+        queue: VecDeque<u8>,
+        #[stor(collection = "line")]
+        log: String,
+        #[stor(collection)]
+        ordered: BTreeMap<u8, u8>,
+        // names that the singular rule would get wrong
+        #[stor(collection = "address")]
+        addresses: Vec<String>,
+        #[stor(collection = "status")]
+        status: HashSet<u8>,
+    }
+
+    #[test]
+    fn collection_helpers() {
+        let mut r = Registry {
+            id: 0,
+            items: Vec::new(),
+            entries: HashMap::new(),
+            peer_list: HashSet::new(),
+            queue: VecDeque::new(),
+            log: String::new(),
+            ordered: BTreeMap::new(),
+            addresses: Vec::new(),
+            status: HashSet::new(),
+        };
+        r.set_id(1);
+        // the collection helpers replace the setter
+        assert_eq!(r.set_items(()), "skipped");
+        r.push_item(1);
+        r.extend_items(vec![2, 3]);
+        assert_eq!(r.items_len(), 3);
+        r.clear_items();
+        assert_eq!(r.items_len(), 0);
+        assert_eq!(r.insert_entry("a".to_owned(), 1), None);
+        assert_eq!(r.insert_entry("a".to_owned(), 2), Some(1));
+        r.extend_entries(vec![("b".to_owned(), 3)]);
+        // keys can be removed through borrowed forms
+        assert_eq!(r.remove_entry("a"), Some(2));
+        assert_eq!(r.entries_len(), 1);
+        assert!(r.insert_peer("sayan".to_owned()));
+        assert!(!r.insert_peer("sayan".to_owned()));
+        assert!(r.remove_peer("sayan"));
+        assert_eq!(r.peer_list_len(), 0);
+        r.push_queue(1);
+        r.push_queue(2);
+        assert_eq!(r.queue.pop_front(), Some(1));
+        r.push_line("hello");
+        r.extend_log(", world".chars());
+        assert_eq!(r.log, "hello, world");
+        assert_eq!(r.log_len(), 12);
+        r.insert_ordered(1, 1);
+        assert_eq!(r.remove_ordered(&1), Some(1));
+        assert_eq!(r.ordered_len(), 0);
+        r.push_address("127.0.0.1".to_owned());
+        assert_eq!(r.addresses_len(), 1);
+        assert!(r.insert_status(1));
+        assert!(r.remove_status(&1));
+        assert_eq!(r.status_len(), 0);
+    }
+
+    /// The singular forms of these names are keywords
+    #[derive(Stor)]
+    #[stor(collection)]
+    struct Keywords {
+        types: Vec<String>,
+        refs: HashSet<u8>,
+        #[stor(collection = "fn")]
+        callbacks: Vec<u8>,
+    }
+
+    #[test]
+    fn keyword_singulars() {
+        let mut k = Keywords {
+            types: vec![],
+            refs: HashSet::new(),
+            callbacks: vec![],
+        };
+        k.push_type("u8".to_owned());
+        assert!(k.insert_ref(1));
+        assert!(k.remove_ref(&1));
+        k.push_fn(2);
+        assert_eq!((k.types_len(), k.refs_len(), k.callbacks_len()), (1, 0, 1));
+    }
+
+    #[derive(Stor)]
+    #[stor(set, collection)]
+    struct Everything {
+        names: Vec<String>,
+        full: std::collections::BTreeSet<u8>,
+        bounded: bounded::Vec<u8>,
+        other: u8,
+    }
+
+    mod bounded {
+        /// Not the standard library type, despite its name
+        #[derive(Default)]
+        pub struct Vec<T>(pub Option<T>);
+    }
+
+    #[test]
+    fn struct_level_collections() {
+        let mut e = Everything {
+            names: vec![],
+            full: Default::default(),
+            bounded: Default::default(),
+            other: 0,
+        };
+        assert!(e.insert_full(1));
+        // only the setter is generated for the other `Vec`
+        e.set_bounded(bounded::Vec(Some(1)));
+        assert_eq!(e.bounded.0, Some(1));
+        e.push_name("a".to_owned());
+        e.set_names(vec![]);
+        e.set_other(1);
+        assert_eq!(e.names_len(), 0);
+        assert_eq!(e.other, 1);
+    }
+}
+
+mod atomics {
+    use bagel::{Gtor, Stor};
+    use core::ptr;
+    use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, Ordering};
+
+    #[derive(Gtor, Stor)]
+    struct Explicit {
+        count: AtomicU64,
+        flag: AtomicBool,
+        ptr: AtomicPtr<u8>,
+    }
+
+    #[test]
+    fn explicit_orderings() {
+        let mut byte = 0u8;
+        let e = Explicit {
+            count: AtomicU64::new(0),
+            flag: AtomicBool::new(false),
+            ptr: AtomicPtr::new(ptr::null_mut()),
+        };
+        e.store_count(1, Ordering::Release);
+        assert_eq!(e.fetch_add_count(2, Ordering::AcqRel), 1);
+        assert_eq!(e.swap_count(5, Ordering::AcqRel), 3);
+        assert_eq!(
+            e.compare_exchange_count(4, 6, Ordering::AcqRel, Ordering::Acquire),
+            Err(5)
+        );
+        assert_eq!(e.load_count(Ordering::Acquire), 5);
+        assert!(!e.swap_flag(true, Ordering::SeqCst));
+        assert!(e.load_flag(Ordering::SeqCst));
+        e.store_ptr(&mut byte, Ordering::SeqCst);
+        assert_eq!(e.load_ptr(Ordering::SeqCst), &mut byte as *mut u8);
+    }
+
+    #[derive(Gtor, Stor)]
+    #[gtor(ordering = "AcqRel")]
+    #[stor(ordering = "AcqRel")]
+    struct Defaulted {
+        count: AtomicU64,
+        flag: AtomicBool,
+        other: u8,
+    }
+
+    #[test]
+    fn default_ordering() {
+        let mut d = Defaulted {
+            count: AtomicU64::new(0),
+            flag: AtomicBool::new(false),
+            other: 0,
+        };
+        // `AcqRel` is not valid for loads and stores, so they use `Acquire` and `Release`
+        d.store_count(1);
+        assert_eq!(d.fetch_add_count(1), 1);
+        assert_eq!(d.compare_exchange_count(2, 3), Ok(2));
+        assert_eq!(d.load_count(), 3);
+        assert_eq!(d.compare_exchange_flag(true, false), Err(false));
+        assert!(!d.load_flag());
+        // other fields are unaffected
+        d.set_other(1);
+        assert_eq!(d.get_other(), 1);
+    }
+
+    mod custom {
+        /// Not the standard library type, despite its name
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        pub struct AtomicU8(pub u8);
+    }
+
+    #[derive(Gtor, Stor)]
+    struct Paths {
+        full: std::sync::atomic::AtomicU8,
+        custom: custom::AtomicU8,
+    }
+
+    #[test]
+    fn std_paths_only() {
+        let mut p = Paths {
+            full: std::sync::atomic::AtomicU8::new(0),
+            custom: custom::AtomicU8(0),
+        };
+        p.store_full(1, Ordering::SeqCst);
+        assert_eq!(p.load_full(Ordering::SeqCst), 1);
+        p.set_custom(custom::AtomicU8(2));
+        assert_eq!(*p.get_custom(), custom::AtomicU8(2));
+    }
+}
+
+mod inner_accessors {
+    use bagel::{Gtor, Stor};
+    use std::cell::{Cell, RefCell};
+    use std::sync::{Mutex, RwLock};
+
+    #[derive(Gtor)]
+    #[gtor(get, inner)]
+    struct Shared<T> {
+        id: u8,
+        count: Cell<usize>,
+        generic: Cell<T>,
+        #[gtor_copy]
+        pair: Cell<(u8, u8)>,
+        name: Cell<String>,
+        retries: Cell<Option<u32>>,
+        log: RefCell<Vec<u8>>,
+        queue: Mutex<Vec<u8>>,
+        config: RwLock<u8>,
+    }
+
+    #[test]
+    fn lock_and_cell_accessors() {
+        let s = Shared {
+            id: 1,
+            count: Cell::new(0),
+            generic: Cell::new(1u64),
+            pair: Cell::new((0, 0)),
+            name: Cell::new(String::new()),
+            retries: Cell::new(None),
+            log: RefCell::new(vec![]),
+            queue: Mutex::new(vec![]),
+            config: RwLock::new(0),
+        };
+        assert_eq!(s.get_id(), 1);
+        s.set_count(s.get_count() + 1);
+        assert_eq!(s.get_count(), 1);
+        s.set_generic(2);
+        assert_eq!(s.get_generic(), 2);
+        s.set_pair((1, 2));
+        assert_eq!(s.get_pair(), (1, 2));
+        // the contents aren't known to be `Copy`, so the getters return the cells
+        s.set_name("sayan".to_owned());
+        assert_eq!(s.get_name().take(), "sayan");
+        s.set_retries(Some(3));
+        assert_eq!(s.get_retries().get(), Some(3));
+        s.borrow_log_mut().push(1);
+        assert_eq!(*s.borrow_log(), vec![1]);
+        // the wrappers are still available by reference
+        assert_eq!(s.get_log().borrow().len(), 1);
+        s.lock_queue().unwrap().push(2);
+        assert_eq!(*s.lock_queue().unwrap(), vec![2]);
+        *s.write_config().unwrap() = 3;
+        assert_eq!(*s.read_config().unwrap(), 3);
+    }
+
+    #[derive(Gtor)]
+    struct FieldLevel {
+        #[gtor(inner)]
+        count: Cell<u8>,
+        other: Cell<u8>,
+    }
+
+    #[test]
+    fn field_level_inner() {
+        let f = FieldLevel {
+            count: Cell::new(1),
+            other: Cell::new(2),
+        };
+        assert_eq!(f.get_count(), 1);
+        assert_eq!(f.get_other().get(), 2);
+    }
+
+    mod spin {
+        /// A lock that isn't the one in `std`
+        pub struct Mutex<T>(pub T);
+
+        impl<T> Mutex<T> {
+            pub fn lock(&self) -> &T {
+                &self.0
+            }
+        }
+    }
+
+    trait NotGeneratedLock {
+        fn lock_spin(&self) -> &'static str {
+            "skipped"
+        }
+    }
+    impl<T> NotGeneratedLock for T {}
+
+    #[derive(Gtor)]
+    #[gtor(get, inner)]
+    struct Locks {
+        spin: spin::Mutex<u8>,
+        std: std::sync::Mutex<u8>,
+        cell: core::cell::Cell<u8>,
+    }
+
+    #[test]
+    fn only_std_wrappers() {
+        let l = Locks {
+            spin: spin::Mutex(1),
+            std: Mutex::new(2),
+            cell: Cell::new(3),
+        };
+        assert_eq!(l.lock_spin(), "skipped");
+        assert_eq!(*l.get_spin().lock(), 1);
+        assert_eq!(*l.lock_std().unwrap(), 2);
+        assert_eq!(l.get_cell(), 3);
+    }
+
+    #[derive(Gtor, Stor)]
+    #[gtor(inner)]
+    #[stor(set, replace)]
+    struct WithStor {
+        count: Cell<u8>,
+    }
+
+    #[test]
+    fn cell_setters_with_stor() {
+        let mut w = WithStor {
+            count: Cell::new(1),
+        };
+        // `Stor` leaves `set_count` to `Gtor`, and still generates the other setters
+        w.set_count(2);
+        assert_eq!(w.get_count(), 2);
+        assert_eq!(w.replace_count(Cell::new(3)).get(), 2);
+        assert_eq!(w.get_count(), 3);
+    }
+}
+
+mod packed {
+    use bagel::{Constdef, Gtor, Stor};
+
+    #[derive(Gtor, Stor)]
+    #[repr(C, packed)]
+    #[gtor(get, into)]
+    #[stor(set, replace, take, update)]
+    struct Header {
+        tag: u8,
+        len: u64,
+        checksum: Option<u32>,
+        #[bagel(skip)]
+        name: String,
+    }
+
+    #[test]
+    fn unaligned_accessors() {
+        let mut h = Header {
+            tag: 1,
+            len: 0,
+            checksum: None,
+            name: "header".to_owned(),
+        };
+        h.set_len(10);
+        assert_eq!(h.get_len(), 10);
+        assert_eq!(h.replace_len(20), 10);
+        h.update_len(|len| *len += 1);
+        assert_eq!(h.take_len(), 21);
+        assert_eq!(h.get_len(), 0);
+        h.set_checksum(0xdead);
+        assert_eq!(h.get_checksum(), Some(0xdead));
+        assert_eq!(h.take_checksum(), Some(0xdead));
+        h.set_checksum(1);
+        h.clear_checksum();
+        assert_eq!(h.get_checksum(), None);
+        assert_eq!(h.get_tag(), 1);
+        assert_eq!(h.into_tag(), 1);
+    }
+
+    #[derive(Constdef, Gtor, Stor)]
+    #[repr(C, packed(2))]
+    #[gtor_const]
+    #[stor_const]
+    #[stor(set, take)]
+    struct Config {
+        flags: u8,
+        limit: u32,
+    }
+
+    const CONFIG: Config = {
+        let mut config = Config::default();
+        config.set_limit(100);
+        config.set_flags(config.get_flags() | 1);
+        config
+    };
+
+    #[test]
+    fn const_unaligned_accessors() {
+        assert_eq!(CONFIG.get_limit(), 100);
+        assert_eq!(CONFIG.get_flags(), 1);
+        let mut config = CONFIG;
+        assert_eq!(config.take_limit(), 100);
+        assert_eq!(config.get_limit(), 0);
+    }
+}
+
+mod field_attrs {
+    use bagel::{Constdef, Ctor, Gtor, Stor};
+
+    trait NotGenerated {
+        fn get_disabled(&self) -> &'static str {
+            "skipped"
+        }
+    }
+    impl<T> NotGenerated for T {}
+
+    #[derive(Constdef, Ctor, Gtor, Stor)]
+    struct Conditional {
+        id: u8,
+        #[cfg(test)]
+        enabled: u8,
+        #[cfg(not(test))]
+        disabled: u8,
+        #[deprecated(note = "use `id`")]
+        #[doc(hidden)]
+        legacy_id: u8,
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn cfg_and_deprecated_fields() {
+        let mut c = Conditional::new(1, 2, 3);
+        assert_eq!(c.get_id(), 1);
+        assert_eq!(c.get_enabled(), 2);
+        assert_eq!(c.get_disabled(), "skipped");
+        c.set_legacy_id(4);
+        assert_eq!(c.get_legacy_id(), 4);
+        assert_eq!(Conditional::default().get_enabled(), 0);
+    }
+
+    #[derive(Constdef)]
+    struct ConditionalTuple(u8, #[cfg(not(test))] &'static str, #[cfg(test)] u16);
+
+    #[test]
+    fn cfg_tuple_fields() {
+        const T: ConditionalTuple = ConditionalTuple::DEFAULT;
+        assert_eq!((T.0, T.1), (0, 0u16));
+    }
+}
+
+mod docs {
+    use bagel::{Ctor, Gtor, Stor};
+
+    /// The doc comments of the fields are carried over, so this also makes sure that every doc
+    /// attribute that ends up on the generated methods is valid
+    #[derive(Ctor, Gtor, Stor)]
+    #[gtor(
+        get,
+        get_mut,
+        doc = "Gets the {field} of this {struct} (with `{method}`)"
+    )]
+    #[stor(set, replace, doc = "Sets the {field} of this {struct}")]
+    #[deny(missing_docs)]
+    pub struct Session {
+        /// The ID of the session.
+        ///
+        /// Unique for every server
+        id: u64,
+        #[doc = "The user"]
+        user: String,
+        flags: u8,
+    }
+
+    #[test]
+    fn doc_templates() {
+        let mut s = Session::new(1, "sayan".to_owned(), 0);
+        s.set_flags(1);
+        *s.get_user_mut() = "root".to_owned();
+        assert_eq!(s.replace_id(2), 1);
+        assert_eq!(s.get_id(), 2);
+        assert_eq!(s.get_user(), "root");
+        assert_eq!(s.get_flags(), 1);
+    }
+}
+
+mod method_attrs {
+    use bagel::{Ctor, Gtor, Stor};
+
+    #[derive(Ctor, Gtor, Stor)]
+    #[ctor(attrs(inline(always)))]
+    #[gtor(
+        get,
+        clone,
+        attrs(inline(always), must_use = "getters have no side effects")
+    )]
+    #[stor(attrs(inline(never)))]
+    struct Hot {
+        #[gtor(attrs(cold))]
+        id: u64,
+        #[stor(attrs())]
+        name: String,
+    }
+
+    #[test]
+    fn explicit_attrs() {
+        let mut h = Hot::new(1, "hot".to_owned());
+        h.set_id(2);
+        h.set_name("path".to_owned());
+        // `get_id` isn't `#[must_use]`, since the field's list replaces the struct's list
+        h.get_id();
+        assert_eq!(h.get_id(), 2);
+        assert_eq!(h.get_name_cloned(), "path");
+    }
+}
+
+mod constdef_options {
+    use bagel::Constdef;
+
+    #[derive(Constdef, Debug, PartialEq)]
+    #[constdef(fn_name = "const_default", vis = "pub(crate)", no_default_impl)]
+    struct Limits {
+        max_connections: usize,
+        timeout: u64,
+    }
+
+    impl Default for Limits {
+        fn default() -> Self {
+            Self {
+                max_connections: 100,
+                ..Self::const_default()
+            }
+        }
+    }
+
+    #[derive(Constdef)]
+    #[constdef(no_inherent)]
+    struct Buffer(Vec<u8>, usize);
+
+    impl Buffer {
+        fn default() -> Self {
+            Self(Vec::with_capacity(16), 16)
+        }
+    }
+
+    const LIMITS: Limits = Limits::const_default();
+
+    #[test]
+    fn renamed_and_skipped_items() {
+        assert_eq!(LIMITS.max_connections, 0);
+        assert_eq!(Limits::default().max_connections, 100);
+        assert_eq!(<Limits as Constdef>::DEFAULT, LIMITS);
+        let buffer = Buffer::default();
+        assert_eq!(buffer.1, 16);
+        let default: Buffer = Default::default();
+        assert_eq!(default.0.capacity(), 0);
+        assert_eq!(default.1, 0);
+    }
+}
+
+// the parentheses are there on purpose, to make sure that `Constdef` sees through them
+#[allow(unused_parens)]
+mod macro_generated {
+    use bagel::{def, Constdef};
+
+    def! {
+        #[derive(Constdef)]
+        #[constdef(no_default_impl)]
+        pub struct Oven {
+            temperature: u8,
+            increment_by: u8 = 1,
+            trays: [(u8, ((u16))); 2] = [(1, 2), (3, 4)],
+            items: Vec<String> = vec!["bagels".to_owned()],
+        }
+    }
+
+    macro_rules! wrapped {
+        ($name:ident, $ty:ty) => {
+            #[derive(Constdef)]
+            struct $name {
+                plain: $ty,
+                nested: ($ty, [$ty; 2], [($ty,); 1]),
+                parenthesized: ($ty),
+            }
+        };
+    }
+
+    wrapped!(Wrapped, u32);
+
+    const OVEN: Oven = Oven::default();
+    const WRAPPED: Wrapped = Wrapped::default();
+
+    #[test]
+    fn def_with_constdef() {
+        // `def!` implements `Default` with the declared values, while `Constdef` uses the
+        // constant defaults
+        assert_eq!(OVEN.increment_by, 0);
+        assert_eq!(OVEN.trays, [(0, 0), (0, 0)]);
+        assert!(OVEN.items.is_empty());
+        let oven: Oven = Default::default();
+        assert_eq!(oven.increment_by, 1);
+        assert_eq!(oven.trays, [(1, 2), (3, 4)]);
+        assert_eq!(oven.temperature, OVEN.temperature);
+    }
+
+    #[test]
+    fn grouped_types() {
+        assert_eq!(WRAPPED.plain, 0);
+        assert_eq!(WRAPPED.nested, (0, [0, 0], [(0,)]));
+        assert_eq!(WRAPPED.parenthesized, 0);
+    }
+}
+
+mod zeroed {
+    use bagel::{Constdef, Zeroable};
+    use core::marker::PhantomData;
+    use core::num::NonZeroU32;
+    use core::ptr::NonNull;
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    #[derive(Zeroable)]
+    struct Slot<T> {
+        value: T,
+        hits: AtomicU64,
+        marker: PhantomData<String>,
+    }
+
+    #[derive(Constdef)]
+    #[constdef(zeroed)]
+    struct Table {
+        slots: [Slot<(u8, char)>; 4096],
+        bitmap: [u64; 1 << 16],
+        len: usize,
+    }
+
+    #[derive(Constdef, Zeroable)]
+    #[constdef(zeroed)]
+    struct Pair(u8, [bool; 2]);
+
+    #[derive(Constdef, Zeroable)]
+    #[constdef(zeroed)]
+    struct Node {
+        next: Option<NonNull<Node>>,
+        id: Option<NonZeroU32>,
+    }
+
+    #[derive(Constdef, Zeroable)]
+    struct Port {
+        requests: usize,
+        admin: bool,
+    }
+
+    // zero-initialise the ports at once, instead of evaluating every element at compile-time
+    #[derive(Constdef)]
+    #[constdef(zeroed)]
+    struct PortLogger {
+        ports: [Port; 65536],
+        root_pid: usize,
+    }
+
+    static TABLE: Table = Table::default();
+    const PORT_LOGGER: PortLogger = PortLogger::default();
+
+    #[test]
+    fn zero_initialised() {
+        let slot = &TABLE.slots[4095];
+        assert_eq!(slot.value, (0, '\0'));
+        assert_eq!(slot.hits.load(Ordering::Relaxed), 0);
+        assert!(TABLE.bitmap.iter().all(|word| *word == 0));
+        assert_eq!(TABLE.len, 0);
+        let pair = Pair::default();
+        assert_eq!((pair.0, pair.1), (0, [false, false]));
+        let node = Node::default();
+        assert!(node.next.is_none());
+        assert!(node.id.is_none());
+    }
+
+    #[test]
+    fn zeroed_nested_object() {
+        assert_eq!(PORT_LOGGER.ports[0].requests, 0);
+        assert!(!PORT_LOGGER.ports[65535].admin);
+        assert_eq!(PORT_LOGGER.root_pid, 0);
+    }
+}
+
+mod constdef_asserts {
+    use bagel::Constdef;
+
+    const LIMIT: u8 = 8;
+
+    #[derive(Constdef)]
+    #[constdef(assert = "self.min <= self.max", assert = "self.nested.0 < LIMIT")]
+    struct Bounds {
+        min: u8,
+        #[constdef(assert = "self == 0")]
+        max: u8,
+        #[constdef(assert = "self.is_empty()")]
+        label: String,
+        #[constdef(assert = "self.1 .0 == 0")]
+        nested: (u8, (u8,)),
+    }
+
+    #[derive(Constdef)]
+    #[constdef(zeroed)]
+    struct Counter(#[constdef(assert = "self < LIMIT")] u8, bool);
+
+    #[test]
+    fn asserts_hold() {
+        let bounds = Bounds::default();
+        assert!(bounds.min <= bounds.max);
+        assert!(bounds.label.is_empty());
+        assert_eq!(bounds.nested, (0, (0,)));
+        let counter = Counter::default();
+        assert_eq!((counter.0, counter.1), (0, false));
+    }
+}
+
+mod reset {
+    use bagel::{Constdef, Reset};
+
+    #[derive(Constdef)]
+    struct Connection {
+        #[constdef(reset)]
+        requests: usize,
+        #[constdef(reset)]
+        flags: (bool, [u8; 2]),
+        buffer: Vec<u8>,
+    }
+
+    trait NotGenerated {
+        fn reset_buffer(&mut self) -> &'static str {
+            "skipped"
+        }
+    }
+
+    impl<T> NotGenerated for T {}
+
+    #[derive(Constdef)]
+    #[constdef(zeroed, reset)]
+    struct Stats {
+        hits: u64,
+        misses: [u32; 4],
+    }
+
+    #[test]
+    fn reset_fields() {
+        let mut con = Connection {
+            requests: 10,
+            flags: (true, [1, 2]),
+            buffer: Vec::with_capacity(64),
+        };
+        con.reset_requests();
+        con.reset_flags();
+        assert_eq!(con.requests, 0);
+        assert_eq!(con.flags, (false, [0, 0]));
+        assert_eq!(con.buffer.capacity(), 64);
+        assert_eq!(NotGenerated::reset_buffer(&mut con), "skipped");
+        let mut stats = Stats {
+            hits: 1,
+            misses: [1; 4],
+        };
+        stats.reset_misses();
+        assert_eq!((stats.hits, stats.misses), (1, [0; 4]));
+        stats.reset_hits();
+        assert_eq!(stats.hits, 0);
+    }
+
+    #[test]
+    fn reset_everything() {
+        let mut con = Connection {
+            requests: 10,
+            flags: (true, [1, 2]),
+            buffer: vec![1, 2, 3],
+        };
+        con.reset();
+        assert_eq!(con.requests, 0);
+        assert!(con.buffer.is_empty());
+        let mut name = String::from("bagel");
+        name.reset();
+        assert!(name.is_empty());
+    }
+}
+
+mod hash_maps {
+    use bagel::hash::{FnvBuildHasher, FnvHashMap, FnvHashSet, FnvHasher};
+    use bagel::Constdef;
+    use std::collections::HashMap;
+    use std::hash::Hasher;
+    use std::sync::RwLock;
+
+    static ROUTES: RwLock<FnvHashMap<&str, u16>> = Constdef::DEFAULT;
+
+    #[derive(Constdef)]
+    struct Registry {
+        names: FnvHashSet<String>,
+        ports: HashMap<u16, String, FnvBuildHasher>,
+    }
+
+    #[test]
+    fn fnv_hash() {
+        let mut hasher = FnvHasher::new();
+        assert_eq!(hasher.finish(), 0xcbf29ce484222325);
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn static_map() {
+        ROUTES.write().unwrap().insert("/users", 8080);
+        assert_eq!(ROUTES.read().unwrap().get("/users"), Some(&8080));
+    }
+
+    #[test]
+    fn derived() {
+        let mut registry = Registry::default();
+        assert!(registry.names.is_empty() && registry.ports.is_empty());
+        registry.names.insert("bagel".to_owned());
+        registry.ports.insert(2003, "skyd".to_owned());
+        assert!(registry.names.contains("bagel"));
+        assert_eq!(registry.ports[&2003], "skyd");
+    }
+}
+
+mod unions {
+    use bagel::Constdef;
+    use std::mem::ManuallyDrop;
+
+    #[repr(C)]
+    #[derive(Constdef, Clone, Copy)]
+    union Cell {
+        int: u64,
+        #[constdef(active)]
+        bytes: [u8; 8],
+        pair: (u32, u32),
+    }
+
+    #[derive(Constdef)]
+    #[constdef(reset)]
+    union Number {
+        int: i32,
+        float: f32,
+    }
+
+    #[derive(Constdef)]
+    union Name {
+        len: usize,
+        #[constdef(active)]
+        owned: ManuallyDrop<String>,
+    }
+
+    const CELL: Cell = Cell::default();
+
+    #[test]
+    fn union_defaults() {
+        unsafe {
+            assert_eq!(CELL.bytes, [0; 8]);
+            assert_eq!(Number::default().int, 0);
+        }
+        let mut number = Number { int: 1 };
+        number.reset_float();
+        assert_eq!(unsafe { number.float }, 0.0);
+    }
+
+    #[test]
+    fn manually_drop_active_field() {
+        let mut name = Name::default();
+        assert!(unsafe { name.owned.is_empty() });
+        unsafe { ManuallyDrop::drop(&mut name.owned) };
+        name = Name { len: 3 };
+        assert_eq!(unsafe { name.len }, 3);
+    }
+}
+
+mod assertions {
+    use bagel::assert::{
+        assert_align, assert_eq_size, assert_impl, assert_not_impl, assert_size, const_assert,
+    };
+    use bagel::Constdef;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[repr(C, align(8))]
+    #[derive(Constdef)]
+    struct Header {
+        magic: u32,
+        len: u16,
+    }
+
+    const MAGIC: u32 = 0xCAFE;
+
+    const_assert!(MAGIC != 0);
+    const_assert!(MAGIC <= u16::MAX as u32, "the magic must fit in two bytes");
+    assert_size!(Header, 8);
+    assert_align!(Header, 8);
+    assert_eq_size!(Header, u64);
+    assert_impl!(Header: Send + Sync + Constdef);
+    assert_not_impl!(Rc<Header>: Send);
+    assert_not_impl!(RefCell<Header>: Send + Sync);
+
+    #[test]
+    fn inside_functions() {
+        const_assert!(MAGIC > 0xFF);
+        assert_size!([Header; 2], 16);
+        assert_impl!(Vec<Header>: Send);
+        assert_not_impl!(Header: Clone);
+        assert_eq!(Header::default().magic + Header::default().len as u32, 0);
+    }
+}
+
+mod layout {
+    use bagel::{layout, Constdef};
+
+    #[layout(size = 16, align = 8)]
+    #[repr(C)]
+    #[derive(Constdef)]
+    struct Header {
+        #[layout(offset = 0)]
+        magic: u32,
+        version: u16,
+        #[layout(offset = 8)]
+        r#type: u64,
+    }
+
+    #[bagel::layout(size = 8)]
+    #[repr(C, packed)]
+    struct Entry(u8, #[layout(offset = 1)] u32, [u8; 3]);
+
+    #[test]
+    fn offsets() {
+        assert_eq!(Header::OFFSET_MAGIC, 0);
+        assert_eq!(Header::OFFSET_VERSION, 4);
+        assert_eq!(Header::OFFSET_TYPE, 8);
+        assert_eq!(Header::default().r#type, 0);
+        assert_eq!(
+            (Entry::OFFSET_0, Entry::OFFSET_1, Entry::OFFSET_2),
+            (0, 1, 5)
+        );
+        let entry = Entry(1, 2, [3; 3]);
+        assert_eq!({ entry.1 } + entry.0 as u32 + entry.2[0] as u32, 6);
+    }
+}
+
+mod hashing {
+    use bagel::hash::{self, hash, FnvHasher};
+    use std::hash::Hasher;
+    use std::hint::black_box;
+
+    const INPUTS: &[&str] = &[
+        "",
+        "a",
+        "abc",
+        "SET",
+        "123456789",
+        "Nobody inspects the spammish repetition",
+    ];
+
+    #[test]
+    fn known_values() {
+        const FNV32: u32 = hash::fnv1a_32(b"a");
+        const FNV64: u64 = hash::fnv1a_64(b"a");
+        const XXH64: u64 = hash::xxh64(b"Nobody inspects the spammish repetition", 0);
+        const CRC32: u32 = hash::crc32(b"123456789");
+        const CRC32C: u32 = hash::crc32c(b"123456789");
+        assert_eq!(FNV32, 0xe40c292c);
+        assert_eq!(FNV64, 0xaf63dc4c8601ec8c);
+        assert_eq!(hash::xxh64(b"", 0), 0xef46db3751d8e999);
+        assert_eq!(hash::xxh64(b"abc", 0), 0x44bc2cf5ad770999);
+        assert_eq!(XXH64, 0xfbcea83c8a378bf1);
+        assert_eq!(CRC32, 0xcbf43926);
+        assert_eq!(CRC32C, 0xe3069283);
+    }
+
+    #[test]
+    fn const_matches_runtime() {
+        const SET: [u64; 3] = [
+            hash::fnv1a_64(b"SET"),
+            hash::xxh64(b"SET", 0),
+            hash::crc32(b"SET") as u64,
+        ];
+        let set = black_box(String::from("SET"));
+        let set = set.as_bytes();
+        assert_eq!(
+            SET,
+            [
+                hash::fnv1a_64(set),
+                hash::xxh64(set, 0),
+                hash::crc32(set) as u64
+            ]
+        );
+        for input in INPUTS {
+            let mut hasher = FnvHasher::new();
+            hasher.write(black_box(input.as_bytes()));
+            assert_eq!(hasher.finish(), hash::fnv1a_64(input.as_bytes()));
+        }
+        let long = black_box(vec![0xab; 1000]);
+        assert_ne!(hash::xxh64(&long, 0), hash::xxh64(&long, 1));
+    }
+
+    #[test]
+    fn macro_matches_const() {
+        assert_eq!(hash!("SET"), hash::fnv1a_64(b"SET"));
+        assert_eq!(hash!(fnv1a_64, b"SET"), hash::fnv1a_64(b"SET"));
+        assert_eq!(hash!(fnv1a_32, "SET"), hash::fnv1a_32(b"SET"));
+        assert_eq!(hash!(crc32, "123456789"), hash::crc32(b"123456789"));
+        assert_eq!(hash!(crc32c, "123456789"), hash::crc32c(b"123456789"));
+        assert_eq!(hash!(xxh64, ""), hash::xxh64(b"", 0));
+        assert_eq!(hash!(xxh64, "abc"), hash::xxh64(b"abc", 0));
+        assert_eq!(
+            hash!(xxh64, "Nobody inspects the spammish repetition"),
+            hash::xxh64(b"Nobody inspects the spammish repetition", 0)
+        );
+        assert_eq!(
+            hash!(
+                xxh64,
+                "the quick brown fox jumps over the lazy dog, twice over!"
+            ),
+            hash::xxh64(
+                b"the quick brown fox jumps over the lazy dog, twice over!",
+                0
+            )
+        );
+    }
+
+    #[test]
+    fn dispatch_table() {
+        fn dispatch(command: &str) -> u8 {
+            match hash::fnv1a_64(command.as_bytes()) {
+                hash!("GET") => 1,
+                hash!("SET") => 2,
+                _ => 0,
+            }
+        }
+        assert_eq!(INPUTS.iter().map(|input| dispatch(input)).sum::<u8>(), 2);
+        assert_eq!(dispatch("GET"), 1);
+    }
+}
+
+mod static_maps {
+    use bagel::map::{static_map, StaticMap};
+
+    #[derive(Debug, PartialEq)]
+    enum Action {
+        Get,
+        Set,
+    }
+
+    const KEYWORDS: &[&str] = &[
+        "SELECT", "INSERT", "UPDATE", "DELETE", "CREATE", "DROP", "ALTER", "TABLE", "INDEX",
+        "FROM", "WHERE", "AND", "OR", "NOT", "NULL", "IS", "IN", "LIKE", "LIMIT", "OFFSET",
+        "ORDER", "BY", "GROUP", "HAVING", "JOIN", "INNER", "OUTER", "LEFT", "RIGHT", "ON", "AS",
+        "DISTINCT", "COUNT", "SUM", "AVG", "MIN", "MAX", "UNION", "ALL", "EXISTS", "BETWEEN",
+        "CASE", "WHEN", "THEN", "ELSE", "END", "INTO", "VALUES", "SET",
+    ];
+
+    static SQL: StaticMap<&str, usize> = static_map! {
+        #![ignore_ascii_case]
+        "SELECT" => 0,
+        "INSERT" => 1,
+        "UPDATE" => 2,
+        "DELETE" => 3,
+        "CREATE" => 4,
+        "DROP" => 5,
+        "ALTER" => 6,
+        "TABLE" => 7,
+        "INDEX" => 8,
+        "FROM" => 9,
+        "WHERE" => 10,
+        "AND" => 11,
+        "OR" => 12,
+        "NOT" => 13,
+        "NULL" => 14,
+        "IS" => 15,
+        "IN" => 16,
+        "LIKE" => 17,
+        "LIMIT" => 18,
+        "OFFSET" => 19,
+        "ORDER" => 20,
+        "BY" => 21,
+        "GROUP" => 22,
+        "HAVING" => 23,
+        "JOIN" => 24,
+        "INNER" => 25,
+        "OUTER" => 26,
+        "LEFT" => 27,
+        "RIGHT" => 28,
+        "ON" => 29,
+        "AS" => 30,
+        "DISTINCT" => 31,
+        "COUNT" => 32,
+        "SUM" => 33,
+        "AVG" => 34,
+        "MIN" => 35,
+        "MAX" => 36,
+        "UNION" => 37,
+        "ALL" => 38,
+        "EXISTS" => 39,
+        "BETWEEN" => 40,
+        "CASE" => 41,
+        "WHEN" => 42,
+        "THEN" => 43,
+        "ELSE" => 44,
+        "END" => 45,
+        "INTO" => 46,
+        "VALUES" => 47,
+        "SET" => 48,
+    };
+
+    static ACTIONS: StaticMap<&str, Action> = static_map! {
+        "GET" => Action::Get,
+        "SET" => Action::Set,
+    };
+
+    const TAGS: StaticMap<&[u8], char> = static_map! {
+        b"\x00" => '0',
+        b"\xff\xfe" => 'f',
+        b"" => 'e',
+    };
+
+    static CODES: StaticMap<i16, &str> = static_map! {
+        -1 => "error",
+        0 => "ok",
+        -32768 => "min",
+    };
+
+    static BIG: StaticMap<u128, u8> = static_map! {
+        340282366920938463463374607431768211455 => 1,
+        0 => 0,
+    };
+
+    static EMPTY: StaticMap<u8, u8> = static_map! {};
+
+    const SET: Option<&Action> = ACTIONS.get("SET");
+
+    #[test]
+    fn lookups() {
+        assert_eq!(SET, Some(&Action::Set));
+        assert_eq!(ACTIONS.get("GET"), Some(&Action::Get));
+        assert_eq!(ACTIONS.get("get"), None);
+        assert_eq!(ACTIONS.get("DEL"), None);
+        assert_eq!(ACTIONS.len(), 2);
+        assert_eq!(TAGS.get(b"\xff\xfe"), Some(&'f'));
+        assert_eq!(TAGS.get(b""), Some(&'e'));
+        assert!(!TAGS.contains_key(b"\xfe\xff"));
+        assert_eq!(CODES.get(-1), Some(&"error"));
+        assert_eq!(CODES.get(1), None);
+        assert_eq!(CODES.get(i16::MIN), Some(&"min"));
+        assert_eq!(BIG.get(u128::MAX), Some(&1));
+        assert_eq!(BIG.get(0), Some(&0));
+        assert!(EMPTY.is_empty() && EMPTY.get(0).is_none());
+    }
+
+    #[test]
+    fn perfect_hash() {
+        assert_eq!(SQL.len(), KEYWORDS.len());
+        for (i, keyword) in KEYWORDS.iter().enumerate() {
+            assert_eq!(SQL.get(keyword), Some(&i));
+            assert_eq!(SQL.get(&keyword.to_lowercase()), Some(&i));
+        }
+        assert_eq!(SQL.get("SeLeCt"), Some(&0));
+        assert!(!SQL.contains_key("SELECTS"));
+        assert!(SQL.entries().iter().all(|(key, _)| KEYWORDS.contains(key)));
+    }
+}
+
+mod const_strings {
+    use bagel::{const_concat, const_format};
+
+    const PREFIX: &str = "/api";
+    const NAME: &str = "users";
+    const VERSION: u8 = 2;
+    const OFFSET: i32 = -40;
+    const DEBUG: bool = true;
+
+    const PATH: &str = const_concat!(PREFIX, "/", NAME);
+    const ROUTE: &str = const_format!("{}/v{}/{}", PREFIX, VERSION, NAME);
+
+    #[test]
+    fn concat() {
+        assert_eq!(PATH, "/api/users");
+        assert_eq!(const_concat!(), "");
+        assert_eq!(const_concat!(DEBUG, !DEBUG,), "truefalse");
+        assert_eq!(const_concat!(OFFSET, " ", 0u8, " ", 9usize), "-40 0 9");
+        assert_eq!(const_concat!("ü", "ß"), "üß");
+        assert_eq!(
+            const_concat!(i128::MIN, " ", u128::MAX),
+            "-170141183460469231731687303715884105728 340282366920938463463374607431768211455"
+        );
+        assert_eq!(const_concat!(i8::MIN, i64::MAX), "-1289223372036854775807");
+        assert_eq!(const_concat!("v", 2, -5), "v2-5");
+        assert_eq!(const_concat!(i32::MIN), "-2147483648");
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(ROUTE, "/api/v2/users");
+        assert_eq!(const_format!(""), "");
+        assert_eq!(const_format!("no args"), "no args");
+        assert_eq!(const_format!("{}", OFFSET), "-40");
+        assert_eq!(const_format!("{{{}}} {{}}", DEBUG), "{true} {}");
+        assert_eq!(const_format!("{}{}", PREFIX, NAME,), "/apiusers");
+        assert_eq!(const_format!("{}", 2), "2");
+        assert_eq!(const_format!("{} {}", NAME.len(), { VERSION + 1 }), "5 3");
+    }
+}
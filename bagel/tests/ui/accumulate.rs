@@ -0,0 +1,24 @@
+//! Every error in the attributes is reported at once, each pointing at the offending tokens
+
+use bagel::{Ctor, Gtor, Stor};
+use std::marker::PhantomData;
+
+#[derive(Gtor, Stor, Ctor)]
+#[gtor_copy]
+#[gtor(get, only(id, missing))]
+#[stor(set, except(nope))]
+struct Account {
+    id: u64,
+    #[gtor_const]
+    name: String,
+    #[gtor_skip]
+    #[gtor(get)]
+    #[stor_const]
+    balance: u64,
+    #[phantom]
+    #[gtor_copy]
+    #[ctor_const]
+    marker: PhantomData<u8>,
+}
+
+fn main() {}
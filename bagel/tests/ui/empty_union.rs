@@ -0,0 +1,8 @@
+//! A union without fields has no default to take, which is reported instead of panicking
+
+use bagel::Constdef;
+
+#[derive(Constdef)]
+union Empty {}
+
+fn main() {}
@@ -0,0 +1,31 @@
+//! Unknown items in the attributes of the derives get a `help:` hint with the closest known item
+
+use bagel::{Constdef, Ctor, Gtor, Stor};
+
+#[derive(Gtor, Stor)]
+#[gtor(gte, clone)]
+#[stor(replce, ordering = "Relaxd")]
+struct Config {
+    #[gtor(get_mutt)]
+    #[stor(colection)]
+    items: Vec<u8>,
+    #[bagel(skp)]
+    port: u16,
+    #[gtor(docs = "...")]
+    host: String,
+}
+
+#[derive(Constdef)]
+#[constdef(fn_nam = "initial")]
+struct Limits {
+    #[constdef(defalt = "1")]
+    max: u8,
+}
+
+#[derive(Ctor)]
+#[ctor(atrs(inline))]
+struct Point {
+    x: u8,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+//! A default ordering that isn't valid for stores is rejected instead of being weakened
+
+use bagel::Stor;
+use std::sync::atomic::AtomicU64;
+
+#[derive(Stor)]
+#[stor(ordering = "Acquire")]
+struct Counter {
+    hits: AtomicU64,
+}
+
+fn main() {}
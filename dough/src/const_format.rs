@@ -0,0 +1,105 @@
+//! This module is responsible for the `const_format!` macro, which splits the format string at
+//! its `{}` placeholders and passes the pieces and the arguments on to `bagel::const_concat!`
+//!
+
+use ::proc_macro::TokenStream;
+use ::quote::quote;
+use ::syn::parse::{Parse, ParseStream};
+use ::syn::punctuated::Punctuated;
+use ::syn::{parse_macro_input, Expr, LitStr, Token};
+
+struct FormatInput {
+    format: LitStr,
+    args: Punctuated<Expr, Token![,]>,
+}
+
+impl Parse for FormatInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let format = input.parse()?;
+        let args = if input.is_empty() {
+            Punctuated::new()
+        } else {
+            input.parse::<Token![,]>()?;
+            input.parse_terminated(Expr::parse)?
+        };
+        Ok(Self { format, args })
+    }
+}
+
+pub(crate) fn const_format(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as FormatInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Returns the pieces of the format string around its `{}` placeholders (so there is always one
+/// more piece than there are placeholders), with `{{` and `}}` unescaped
+fn split(format: &LitStr) -> syn::Result<Vec<String>> {
+    let value = format.value();
+    let mut pieces = vec![String::new()];
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                pieces.last_mut().unwrap().push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                pieces.push(String::new());
+            }
+            ('{', _) => {
+                return Err(syn::Error::new_spanned(
+                    format,
+                    "only `{}` placeholders are supported (use `{{` for a literal `{`)",
+                ))
+            }
+            ('}', _) => {
+                return Err(syn::Error::new_spanned(
+                    format,
+                    "unmatched `}` in the format string (use `}}` for a literal `}`)",
+                ))
+            }
+            (c, _) => pieces.last_mut().unwrap().push(c),
+        }
+    }
+    Ok(pieces)
+}
+
+fn expand(input: FormatInput) -> syn::Result<::proc_macro2::TokenStream> {
+    let pieces = self::split(&input.format)?;
+    let placeholders = pieces.len() - 1;
+    if let Some(extra) = input.args.iter().nth(placeholders) {
+        return Err(syn::Error::new_spanned(
+            extra,
+            format!(
+                "argument never used: the format string has {} `{{}}` placeholder(s)",
+                placeholders
+            ),
+        ));
+    }
+    if input.args.len() < placeholders {
+        return Err(syn::Error::new_spanned(
+            &input.format,
+            format!(
+                "the format string has {} `{{}}` placeholder(s), but {} argument(s) were given",
+                placeholders,
+                input.args.len()
+            ),
+        ));
+    }
+    let mut parts = Vec::new();
+    for (i, piece) in pieces.iter().enumerate() {
+        if !piece.is_empty() {
+            let piece = LitStr::new(piece, input.format.span());
+            parts.push(quote! { #piece });
+        }
+        if let Some(arg) = input.args.iter().nth(i) {
+            parts.push(quote! { #arg });
+        }
+    }
+    Ok(quote! {
+        ::bagel::const_concat!(#(#parts),*)
+    })
+}
@@ -0,0 +1,124 @@
+//! Compile-time checks on the default value, set with `#[constdef(assert = "...")]`
+
+use crate::util::Diagnostics;
+use ::proc_macro2::{Group, Ident, Span, TokenStream, TokenTree};
+use ::quote::{quote, quote_spanned, ToTokens};
+use ::syn::{Data, DeriveInput, Expr, Lit, LitStr, Member};
+
+/// The binding that holds the default value inside the generated check
+const DEFAULT_BINDING: &str = "__bagel_default";
+
+/// An `assert = "..."` item
+pub(crate) struct Assert {
+    /// The string literal, which is kept for error spans and the panic message
+    lit: LitStr,
+    /// The parsed expression, with every token spanned at the literal
+    expr: TokenStream,
+}
+
+impl Assert {
+    pub(crate) fn parse(lit: &Lit, diag: &mut Diagnostics) -> Option<Self> {
+        let lit = match lit {
+            Lit::Str(lit) => lit,
+            lit => {
+                diag.error(lit, "expected a string literal");
+                return None;
+            }
+        };
+        match lit.parse::<Expr>() {
+            Ok(expr) => Some(Self {
+                lit: lit.clone(),
+                expr: expr.into_token_stream(),
+            }),
+            Err(_) => {
+                diag.error(
+                    lit,
+                    "expected a boolean expression, like `self.min <= self.max`",
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Replaces every `self` in the expression with the given tokens
+fn replace_self(tokens: TokenStream, with: &TokenStream) -> TokenStream {
+    tokens
+        .into_iter()
+        .flat_map(|tt| match tt {
+            TokenTree::Ident(ident) if ident == "self" => with.clone(),
+            TokenTree::Group(group) => {
+                let mut new =
+                    Group::new(group.delimiter(), self::replace_self(group.stream(), with));
+                new.set_span(group.span());
+                TokenTree::Group(new).into()
+            }
+            tt => tt.into(),
+        })
+        .collect()
+}
+
+/// Returns a `const _` item that fails to compile if any of the checks is false for the default
+/// value. Checks on the container see the default value as `self`, while checks on a field see
+/// the value of that field as `self`
+pub(crate) fn expand(
+    ast: &DeriveInput,
+    container: &[Assert],
+    fields: &[(Member, TokenStream, Assert)],
+    diag: &mut Diagnostics,
+) -> TokenStream {
+    if container.is_empty() && fields.is_empty() {
+        return quote! {};
+    }
+    let unsupported = if let Data::Union(_) = ast.data {
+        // reading the fields of a union is unsafe
+        Some("`assert` is not supported on unions")
+    } else if !ast.generics.params.is_empty() {
+        // a `const _` item cannot name the generic parameters
+        Some("`assert` is not supported on generic types")
+    } else {
+        None
+    };
+    if let Some(message) = unsupported {
+        let lits = container
+            .iter()
+            .chain(fields.iter().map(|(_, _, assert)| assert));
+        for assert in lits {
+            diag.error(&assert.lit, message);
+        }
+        return quote! {};
+    }
+    let struct_name = &ast.ident;
+    let binding = Ident::new(DEFAULT_BINDING, Span::call_site());
+    let check = |assert: &Assert, value: TokenStream| {
+        let expr = self::replace_self(assert.expr.clone(), &value);
+        // the message is passed on as a format string
+        let message = format!(
+            "`Constdef` assertion failed for `{}`: {}",
+            struct_name,
+            assert.lit.value().replace('{', "{{").replace('}', "}}")
+        );
+        let span = assert.lit.span();
+        let message = LitStr::new(&message, span);
+        // point a failed check at the `assert = "..."` that it came from
+        quote_spanned! {span=> ::core::assert!(#expr, #message); }
+    };
+    let mut checks = quote! {};
+    for assert in container {
+        let check = check(assert, quote! { #binding });
+        checks = quote! { #checks #check };
+    }
+    for (member, cfgs, assert) in fields {
+        let check = check(assert, quote! { (#binding.#member) });
+        checks = quote! { #checks #cfgs #check };
+    }
+    quote! {
+        const _: () = {
+            let #binding = <#struct_name as ::bagel::Constdef>::DEFAULT;
+            #checks
+            // the default may own values that cannot be dropped in a const context
+            #[allow(clippy::forget_non_drop)]
+            ::core::mem::forget(#binding);
+        };
+    }
+}
@@ -1,127 +1,229 @@
+mod asserts;
+mod options;
 mod type_processor;
 mod utils;
 
+use crate::util;
+use crate::util::Diagnostics;
+use crate::zeroable;
 use ::proc_macro::TokenStream;
-use ::quote::quote;
-use ::syn::{parse_macro_input, spanned::Spanned, DeriveInput, Type};
+use ::quote::{format_ident, quote};
+use ::syn::{parse_macro_input, Data, DeriveInput, Field, Ident, Index, Member};
+use options::{FieldOptions, Options};
 use utils::{FieldInfo, NamedFieldInfo, UnnamedFieldInfo};
 
 pub(crate) fn derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
-    let fields = match utils::get_struct_field_names(&ast) {
-        Ok(f) => f,
-        Err(e) => return e,
-    };
-    match fields {
-        FieldInfo::Named(fields) => generate_named(fields, &ast),
-        FieldInfo::Unnamed(fields) => generate_unnamed(fields, &ast),
-    }
+    expand(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
-fn generate_unnamed(fields: Vec<UnnamedFieldInfo<'_>>, ast: &DeriveInput) -> TokenStream {
+fn expand(ast: &DeriveInput) -> syn::Result<::proc_macro2::TokenStream> {
+    let mut diag = Diagnostics::default();
+    let fields = utils::get_struct_field_names(ast)?;
+    let options = Options::parse(&ast.attrs, &mut diag);
+    let items = self::field_items(ast, &options, &mut diag);
+    let asserts = asserts::expand(ast, &options.asserts, &items.asserts, &mut diag);
+    let resets = &items.resets;
     let (impl_gen, ty_gen, where_clause) = ast.generics.split_for_impl();
     let struct_name = &ast.ident;
-    let mut self_args = quote! {};
-    for (ty, _attrs) in fields {
-        let ret: Option<::quote::__private::TokenStream> = match ty {
-            Type::Path(_) => Some(quote! { ::bagel::Constdef::DEFAULT }),
-            Type::Array(arr) => type_processor::recursive_process_array(arr).map(|tokens| {
-                quote! {
-                    [#tokens]
+    // the expression for the default value
+    let (init, allow_deprecated) = match fields {
+        // zero every byte at once, instead of evaluating the default for every field
+        _ if options.zeroed => {
+            let asserts = zeroable::assert_zeroable(&zeroable::field_types(ast)?);
+            let init = quote! {
+                {
+                    #asserts
+                    unsafe { ::core::mem::MaybeUninit::<Self>::zeroed().assume_init() }
                 }
-            }),
-            Type::Tuple(tpl) => type_processor::recursive_process_tuple(tpl).map(|tokens| {
-                quote! {
-                    (#tokens)
+            };
+            (init, quote! {})
+        }
+        FieldInfo::Named(fields) => {
+            let allow_deprecated =
+                util::allow_deprecated(fields.iter().map(|(_, _, attrs)| *attrs));
+            (generate_named(fields, &mut diag), allow_deprecated)
+        }
+        FieldInfo::Unnamed(fields) => {
+            let allow_deprecated = util::allow_deprecated(fields.iter().map(|(_, attrs)| *attrs));
+            (generate_unnamed(fields, &mut diag), allow_deprecated)
+        }
+        FieldInfo::Union(fields) => {
+            // unions default to their first field
+            let (field, ty, attrs) = items
+                .active
+                .and_then(|active| fields.iter().find(|(field, _, _)| *field == active))
+                .unwrap_or(&fields[0]);
+            let allow_deprecated = util::allow_deprecated(std::iter::once(*attrs));
+            let init = match diag.ok(type_processor::process_type(ty)) {
+                Some(value) => quote! { Self { #field: #value } },
+                None => quote! {},
+            };
+            (init, allow_deprecated)
+        }
+    };
+    let inherent_fn = options.inherent_fn();
+    let default_impl = if options.default_impl {
+        quote! {
+            impl #impl_gen ::core::default::Default for #struct_name #ty_gen #where_clause {
+                fn default() -> Self {
+                    ::bagel::Constdef::DEFAULT
                 }
-            }),
-            _ => {
-                return syn::Error::new(ty.span(), "Unsupported type for `Constdef`")
-                    .into_compile_error()
-                    .into()
-            }
-        };
-        if let Some(r) = ret {
-            self_args = quote! {
-                #self_args
-                #r,
             }
-        } else {
-            return syn::Error::new(ty.span(), "Unsupported type for `Constdef`")
-                .into_compile_error()
-                .into();
         }
-    }
-    let tokens = quote! {
+    } else {
+        quote! {}
+    };
+    diag.finish(quote! {
         impl #impl_gen #struct_name #ty_gen #where_clause {
-            pub const fn default() -> Self {
-                Self(#self_args)
-            }
-        }
-        impl #impl_gen ::core::default::Default for #struct_name #ty_gen #where_clause {
-            fn default() -> Self {
-                ::bagel::Constdef::DEFAULT
-            }
+            #inherent_fn
+            #resets
         }
+        #default_impl
         impl #impl_gen ::bagel::Constdef for #struct_name #ty_gen #where_clause {
-            const DEFAULT: Self = #struct_name::default();
+            #allow_deprecated
+            const DEFAULT: Self = #init;
         }
-    };
-    tokens.into()
+        #asserts
+    })
 }
 
-fn generate_named(fields: Vec<NamedFieldInfo<'_>>, ast: &DeriveInput) -> TokenStream {
-    let (impl_gen, ty_gen, where_clause) = ast.generics.split_for_impl();
-    let struct_name = &ast.ident;
-    let mut self_args = quote! {};
-    for (field, ty, _attrs) in fields {
-        let ret: Option<::quote::__private::TokenStream> = match ty {
-            Type::Path(_) => Some(quote! {
-                ::bagel::Constdef::DEFAULT
-            }),
-            Type::Array(arr) => type_processor::recursive_process_array(arr).map(|tokens| {
-                quote! {
-                    [#tokens]
-                }
-            }),
-            Type::Tuple(tpl) => type_processor::recursive_process_tuple(tpl).map(|tokens| {
-                quote! {
-                    (#tokens)
+/// The items set on the fields with `#[constdef(...)]`
+#[derive(Default)]
+struct FieldItems<'a> {
+    /// The checks set on every field, along with the field and its `cfg` attributes
+    asserts: Vec<(Member, ::proc_macro2::TokenStream, asserts::Assert)>,
+    /// The `reset_<field>` methods
+    resets: ::proc_macro2::TokenStream,
+    /// The field of a union marked with `#[constdef(active)]`
+    active: Option<&'a Ident>,
+}
+
+fn field_items<'a>(
+    ast: &'a DeriveInput,
+    options: &Options,
+    diag: &mut Diagnostics,
+) -> FieldItems<'a> {
+    let (fields, is_union): (Vec<&Field>, _) = match &ast.data {
+        Data::Struct(data) => (data.fields.iter().collect(), false),
+        Data::Union(data) => (data.fields.named.iter().collect(), true),
+        Data::Enum(_) => return FieldItems::default(),
+    };
+    let mut items = FieldItems::default();
+    for (i, field) in fields.into_iter().enumerate() {
+        let field_options = FieldOptions::parse(&field.attrs, diag);
+        if let Some(active) = &field_options.active {
+            if !is_union {
+                diag.error(active, "`active` is only supported on unions");
+            } else if items.active.is_some() {
+                diag.error(active, "only one field of a union can be `active`");
+            } else {
+                items.active = field.ident.as_ref();
+            }
+        }
+        let cfgs = util::cfg_attrs(&field.attrs);
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        };
+        for assert in field_options.asserts {
+            items.asserts.push((member.clone(), cfgs.clone(), assert));
+        }
+        let reset = match field_options.reset.as_ref().or(options.reset.as_ref()) {
+            Some(reset) => reset,
+            None => continue,
+        };
+        let ident = match &field.ident {
+            Some(ident) => ident,
+            None => {
+                diag.error(
+                    reset,
+                    "`reset` is only supported on structs with named fields",
+                );
+                // the same error would be reported for every field
+                if field_options.reset.is_none() {
+                    break;
                 }
-            }),
-            _ => {
-                return syn::Error::new(field.span(), "Unsupported type for `Constdef`")
-                    .into_compile_error()
-                    .into()
+                continue;
             }
         };
-        if let Some(r) = ret {
-            self_args = quote! {
-                #self_args
-                #field: #r,
-            };
+        let ty = &field.ty;
+        let value = if options.zeroed {
+            // the field is checked to be zeroable along with the default value
+            quote! { unsafe { ::core::mem::MaybeUninit::<#ty>::zeroed().assume_init() } }
         } else {
-            return syn::Error::new(ty.span(), "Unsupported type for `Constdef`")
-                .into_compile_error()
-                .into();
-        }
+            // if the type is unsupported, the error was already reported for the default value
+            match type_processor::process_type(ty) {
+                Ok(value) => value,
+                Err(_) => continue,
+            }
+        };
+        let method = format_ident!("reset_{}", ident);
+        let doc = if is_union {
+            format!(
+                "Sets `{}` to its constant default, making it the active field",
+                ident
+            )
+        } else {
+            format!(
+                "Restores `{}` to its constant default, leaving the other fields untouched",
+                ident
+            )
+        };
+        let allow_deprecated = util::allow_deprecated(std::iter::once(&field.attrs));
+        let resets = &items.resets;
+        items.resets = quote! {
+            #resets
+            #cfgs
+            #[doc = #doc]
+            #allow_deprecated
+            #[inline]
+            pub fn #method(&mut self) {
+                self.#ident = #value;
+            }
+        };
     }
-    let tokens = quote! {
-        impl #impl_gen #struct_name #ty_gen #where_clause {
-            pub const fn default() -> Self {
-                Self {
-                    #self_args
-                }
+    items
+}
+
+fn generate_unnamed(
+    fields: Vec<UnnamedFieldInfo<'_>>,
+    diag: &mut Diagnostics,
+) -> ::proc_macro2::TokenStream {
+    let mut self_args = quote! {};
+    for (ty, attrs) in fields {
+        if let Some(r) = diag.ok(type_processor::process_type(ty)) {
+            // the initializer only exists if the field does
+            let cfgs = util::cfg_attrs(attrs);
+            self_args = quote! {
+                #self_args
+                #cfgs #r,
             }
         }
-        impl #impl_gen ::core::default::Default for #struct_name #ty_gen #where_clause {
-            fn default() -> Self {
-                ::bagel::Constdef::DEFAULT
-            }
+    }
+    quote! { Self(#self_args) }
+}
+
+fn generate_named(
+    fields: Vec<NamedFieldInfo<'_>>,
+    diag: &mut Diagnostics,
+) -> ::proc_macro2::TokenStream {
+    let mut self_args = quote! {};
+    for (field, ty, attrs) in fields {
+        if let Some(r) = diag.ok(type_processor::process_type(ty)) {
+            // the initializer only exists if the field does
+            let cfgs = util::cfg_attrs(attrs);
+            self_args = quote! {
+                #self_args
+                #cfgs #field: #r,
+            };
         }
-        impl #impl_gen ::bagel::Constdef for #struct_name #ty_gen #where_clause {
-            const DEFAULT: Self = #struct_name::default();
+    }
+    quote! {
+        Self {
+            #self_args
         }
-    };
-    tokens.into()
+    }
 }
@@ -0,0 +1,150 @@
+//! The options that can be set on the container with `#[constdef(...)]`
+
+use super::asserts::Assert;
+use crate::util;
+use crate::util::Diagnostics;
+use ::quote::quote;
+use ::syn::{Attribute, Ident, Lit, Meta, NestedMeta, Visibility};
+
+const ATTR_CONSTDEF: &str = "constdef";
+/// The items accepted by the `#[constdef(...)]` attribute on the container
+const CONSTDEF_LIST_ITEMS: &[&str] = &[
+    "fn_name",
+    "vis",
+    "no_inherent",
+    "no_default_impl",
+    "zeroed",
+    "assert",
+    "reset",
+];
+/// The items accepted by the `#[constdef(...)]` attribute on a field
+const CONSTDEF_FIELD_ITEMS: &[&str] = &["assert", "reset", "active"];
+
+/// The options for the generated items
+pub(crate) struct Options {
+    /// The name of the inherent `const fn` (`default`, unless set with `fn_name = "..."`)
+    pub(crate) fn_name: Ident,
+    /// The visibility of the inherent `const fn` (`pub`, unless set with `vis = "..."`)
+    pub(crate) vis: Visibility,
+    /// False if `no_inherent` is set
+    pub(crate) inherent: bool,
+    /// False if `no_default_impl` is set
+    pub(crate) default_impl: bool,
+    /// True if `zeroed` is set, in which case the default value has every byte set to zero
+    pub(crate) zeroed: bool,
+    /// The checks on the default value, set with `assert = "..."`
+    pub(crate) asserts: Vec<Assert>,
+    /// The `reset` item, if set, in which case every field gets a `reset_<field>` method
+    pub(crate) reset: Option<NestedMeta>,
+}
+
+/// The options set on a field with `#[constdef(...)]`
+#[derive(Default)]
+pub(crate) struct FieldOptions {
+    /// The checks on the default value of the field, set with `assert = "..."`
+    pub(crate) asserts: Vec<Assert>,
+    /// The `reset` item, if set, in which case the field gets a `reset_<field>` method
+    pub(crate) reset: Option<NestedMeta>,
+    /// The `active` item, if set, in which case the default value of a union initialises this
+    /// field
+    pub(crate) active: Option<NestedMeta>,
+}
+
+impl Options {
+    pub(crate) fn parse(attrs: &[Attribute], diag: &mut Diagnostics) -> Self {
+        let mut options = Self {
+            fn_name: Ident::new("default", proc_macro2::Span::call_site()),
+            vis: syn::parse_quote! { pub },
+            inherent: true,
+            default_impl: true,
+            zeroed: false,
+            asserts: Vec::new(),
+            reset: None,
+        };
+        // the items that configure the inherent fn, which make no sense with `no_inherent`
+        let mut inherent_items = Vec::new();
+        for item in util::get_attr_list(attrs, ATTR_CONSTDEF, diag) {
+            match &item {
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("fn_name") => {
+                    match &nv.lit {
+                        Lit::Str(name) => match name.parse() {
+                            Ok(name) => options.fn_name = name,
+                            Err(_) => diag.error(name, "expected a valid identifier"),
+                        },
+                        lit => diag.error(lit, "expected a string literal"),
+                    }
+                    inherent_items.push(item.clone());
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("vis") => {
+                    match &nv.lit {
+                        Lit::Str(vis) => match vis.parse() {
+                            Ok(vis) => options.vis = vis,
+                            Err(_) => diag.error(vis, "expected a visibility, like `pub(crate)`"),
+                        },
+                        lit => diag.error(lit, "expected a string literal"),
+                    }
+                    inherent_items.push(item.clone());
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("no_inherent") => {
+                    options.inherent = false
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("no_default_impl") => {
+                    options.default_impl = false
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("zeroed") => {
+                    options.zeroed = true
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("assert") => {
+                    options.asserts.extend(Assert::parse(&nv.lit, diag))
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("reset") => {
+                    options.reset = Some(item.clone())
+                }
+                _ => util::unknown_nested(&item, ATTR_CONSTDEF, CONSTDEF_LIST_ITEMS, diag),
+            }
+        }
+        if !options.inherent {
+            for item in inherent_items {
+                diag.error(
+                    item,
+                    "configuring the inherent fn has no effect with `no_inherent`",
+                );
+            }
+        }
+        options
+    }
+    /// Returns the inherent `const fn`, if it is generated
+    pub(crate) fn inherent_fn(&self) -> proc_macro2::TokenStream {
+        if !self.inherent {
+            return quote! {};
+        }
+        let (vis, fn_name) = (&self.vis, &self.fn_name);
+        quote! {
+            #[allow(clippy::should_implement_trait)]
+            #vis const fn #fn_name() -> Self {
+                <Self as ::bagel::Constdef>::DEFAULT
+            }
+        }
+    }
+}
+
+impl FieldOptions {
+    pub(crate) fn parse(attrs: &[Attribute], diag: &mut Diagnostics) -> Self {
+        let mut options = Self::default();
+        for item in util::get_attr_list(attrs, ATTR_CONSTDEF, diag) {
+            match &item {
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("assert") => {
+                    options.asserts.extend(Assert::parse(&nv.lit, diag))
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("reset") => {
+                    options.reset = Some(item.clone())
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("active") => {
+                    options.active = Some(item.clone())
+                }
+                _ => util::unknown_nested(&item, ATTR_CONSTDEF, CONSTDEF_FIELD_ITEMS, diag),
+            }
+        }
+        options
+    }
+}
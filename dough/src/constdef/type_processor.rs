@@ -1,61 +1,72 @@
 use quote::quote;
 use syn::{Type, TypeArray, TypeTuple};
 
-pub(super) fn recursive_process_tuple(tuple: &TypeTuple) -> Option<quote::__private::TokenStream> {
+/// Returns an error pointing at the exact type that `Constdef` cannot handle
+fn unsupported(ty: &Type) -> syn::Error {
+    syn::Error::new_spanned(ty, "Unsupported type for `Constdef`")
+}
+
+/// Sees through invisible groups (which is how `macro_rules!` macros pass on `$ty:ty` fragments)
+/// and parentheses around a type
+fn strip(mut ty: &Type) -> &Type {
+    loop {
+        match ty {
+            Type::Group(group) => ty = &group.elem,
+            Type::Paren(paren) => ty = &paren.elem,
+            _ => return ty,
+        }
+    }
+}
+
+/// Returns the constant default expression for a field of the given type
+pub(super) fn process_type(ty: &Type) -> syn::Result<quote::__private::TokenStream> {
+    match self::strip(ty) {
+        Type::Path(_) => Ok(quote! {
+            ::bagel::Constdef::DEFAULT
+        }),
+        Type::Array(arr) => self::recursive_process_array(arr).map(|tokens| {
+            quote! {
+                [#tokens]
+            }
+        }),
+        Type::Tuple(tpl) => self::recursive_process_tuple(tpl).map(|tokens| {
+            quote! {
+                (#tokens)
+            }
+        }),
+        ty => Err(self::unsupported(ty)),
+    }
+}
+
+pub(super) fn recursive_process_tuple(
+    tuple: &TypeTuple,
+) -> syn::Result<quote::__private::TokenStream> {
     let mut inner_decl = quote! {};
+    let mut errors: Option<syn::Error> = None;
     for elem in tuple.elems.iter() {
-        match elem {
-            Type::Path(_) => {
-                inner_decl = quote! {
-                    #inner_decl
-                    ::bagel::Constdef::DEFAULT,
-                };
-            }
-            Type::Tuple(ref tuple) => {
-                let ret = self::recursive_process_tuple(tuple)?;
+        match (self::process_type(elem), &mut errors) {
+            (Ok(ret), _) => {
                 inner_decl = quote! {
                     #inner_decl
                     #ret,
                 };
             }
-            Type::Array(ref arr) => {
-                let ret = self::recursive_process_array(arr)?;
-                inner_decl = quote! {
-                    #inner_decl
-                    [#ret],
-                };
-            }
-            _ => return None,
+            (Err(e), Some(errors)) => errors.combine(e),
+            (Err(e), None) => errors = Some(e),
         }
     }
-    Some(inner_decl)
+    match errors {
+        Some(e) => Err(e),
+        None => Ok(inner_decl),
+    }
 }
 
-pub(super) fn recursive_process_array(array: &TypeArray) -> Option<quote::__private::TokenStream> {
-    let mut inner_decl = quote! {};
+pub(super) fn recursive_process_array(
+    array: &TypeArray,
+) -> syn::Result<quote::__private::TokenStream> {
     let len = &array.len;
-    match &*array.elem {
-        Type::Path(_) => {
-            inner_decl = quote! {
-                #inner_decl
-                ::bagel::Constdef::DEFAULT; #len
-            };
-        }
-        Type::Array(arr) => {
-            let ret = self::recursive_process_array(arr)?;
-            inner_decl = quote! {
-                #inner_decl
-                #ret; #len
-            };
-        }
-        Type::Tuple(ref tuple) => {
-            let ret = self::recursive_process_tuple(tuple)?;
-            inner_decl = quote! {
-                #inner_decl
-                (#ret); #len
-            };
-        }
-        _ => return None,
-    }
-    Some(inner_decl)
+    let elem = self::process_type(&array.elem)?;
+    Ok(quote! {
+        #elem; #len
+    })
 }
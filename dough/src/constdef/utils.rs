@@ -1,4 +1,3 @@
-use ::proc_macro::TokenStream;
 use ::syn::{Attribute, Data, DataStruct, DeriveInput, Fields, Ident, Type};
 
 pub(crate) type NamedFieldInfo<'a> = (&'a Ident, &'a Type, &'a Vec<Attribute>);
@@ -6,11 +5,12 @@ pub(crate) type UnnamedFieldInfo<'a> = (&'a Type, &'a Vec<Attribute>);
 pub(crate) enum FieldInfo<'a> {
     Named(Vec<NamedFieldInfo<'a>>),
     Unnamed(Vec<UnnamedFieldInfo<'a>>),
+    Union(Vec<NamedFieldInfo<'a>>),
 }
 
 /// Returns the field names and their corresponding type from the AST (returning an error
-/// if it isn't a struct)
-pub(crate) fn get_struct_field_names(ast: &DeriveInput) -> Result<FieldInfo<'_>, TokenStream> {
+/// if it is an enum)
+pub(crate) fn get_struct_field_names(ast: &DeriveInput) -> syn::Result<FieldInfo<'_>> {
     match &ast.data {
         Data::Struct(DataStruct {
             fields: Fields::Named(fields),
@@ -35,10 +35,24 @@ pub(crate) fn get_struct_field_names(ast: &DeriveInput) -> Result<FieldInfo<'_>,
                 .map(|field| (&field.ty, &field.attrs))
                 .collect(),
         )),
-        _ => Err(
-            syn::Error::new_spanned(ast, "this macro can only be used on structs")
-                .into_compile_error()
-                .into(),
-        ),
+        // there is no field to take the default from
+        Data::Union(data) if data.fields.named.is_empty() => Err(syn::Error::new_spanned(
+            &data.fields,
+            "this macro can't be used on unions without fields",
+        )),
+        Data::Union(data) => Ok(FieldInfo::Union(
+            data.fields
+                .named
+                .iter()
+                .map(|field| {
+                    let fname = field.ident.as_ref().unwrap();
+                    (fname, &field.ty, &field.attrs)
+                })
+                .collect(),
+        )),
+        _ => Err(syn::Error::new_spanned(
+            ast,
+            "this macro can only be used on structs and unions",
+        )),
     }
 }
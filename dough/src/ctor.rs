@@ -2,78 +2,153 @@
 //!
 
 use crate::util;
-use crate::util::ATTR_PHANTOM;
+use crate::util::{Diagnostics, MethodAttrs, ATTR_BAGEL, ATTR_PHANTOM};
 use ::proc_macro::TokenStream;
 use ::quote::quote;
-use ::syn::{parse_macro_input, DeriveInput};
+use ::syn::{parse_macro_input, Attribute, DeriveInput, Lit, Meta};
 
 const ATTR_CONST_CTOR: &str = "ctor_const";
+const ATTR_CTOR: &str = "ctor";
+/// The items accepted by the `#[ctor(...)]` attribute on the struct
+const CTOR_LIST_ITEMS: &[&str] = &["attrs"];
 
 pub fn derive_ctor(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = parse_macro_input!(input);
-    let struct_name = ast.ident.clone();
+    expand(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(ast: &DeriveInput) -> syn::Result<::proc_macro2::TokenStream> {
+    let mut diag = Diagnostics::default();
+    let struct_name = &ast.ident;
     let (impl_gen, ty_gen, where_clause) = &ast.generics.split_for_impl();
-    let fields = match util::get_struct_field_names(&ast) {
-        Ok(f) => f,
-        Err(e) => return e,
-    };
+    let fields = util::get_struct_field_names(ast)?;
     err_if_subattr_on_primary_attr!(
+        diag,
         "entire struct",
         // a struct cannot be entirely phantom
         ATTR_PHANTOM in ast.attrs,
+        // a struct cannot be entirely skipped
+        ATTR_BAGEL in ast.attrs,
     );
-    let func = ok_else_ret!(util::get_func_header(&ast.attrs, ATTR_CONST_CTOR));
+    let is_const = util::single_instance_of_attr(&ast.attrs, ATTR_CONST_CTOR, &mut diag);
+    let func = util::get_func_header(&ast.attrs, ATTR_CONST_CTOR, &mut diag);
+    // the constructor is `#[inline]` and `#[must_use]`, unless other attributes are given with
+    // `attrs(...)`
+    let mut method_attrs = quote! { #[inline] #[must_use] };
+    for item in util::get_attr_list(&ast.attrs, ATTR_CTOR, &mut diag) {
+        match MethodAttrs::parse(&item, &mut diag) {
+            Some(MethodAttrs::Explicit(attrs)) => method_attrs = attrs,
+            _ => util::unknown_nested(&item, ATTR_CTOR, CTOR_LIST_ITEMS, &mut diag),
+        }
+    }
+    let summary = format!("Creates a new [`{}`]", struct_name);
     if fields.is_empty() {
         // handle fast case: empty struct
-        return {
-            quote! {
-                impl #impl_gen #struct_name #ty_gen #where_clause {
-                    #func new() -> Self {
-                        Self {}
-                    }
+        return diag.finish(quote! {
+            impl #impl_gen #struct_name #ty_gen #where_clause {
+                #[doc = #summary]
+                #method_attrs
+                #func new() -> Self {
+                    Self {}
                 }
             }
+        });
+    }
+    // handle extended case: struct with fields
+    let allow_deprecated = util::allow_deprecated(fields.iter().map(|(_, _, attrs)| *attrs));
+    let mut tokens = quote! {};
+    let mut self_args = quote! {};
+    // the doc comment lists every argument, along with the field's docs
+    let mut arg_docs = Vec::new();
+    for (fname, ty, attrs) in fields {
+        err_if_subattr_on_primary_attr!(
+            diag,
+            "field",
+            // marking const_ctor on a field is invalid
+            ATTR_CONST_CTOR in attrs,
+            ATTR_CTOR in attrs,
+        );
+        let is_phantom = util::single_instance_of_attr(attrs, ATTR_PHANTOM, &mut diag);
+        let is_skipped = util::is_bagel_skipped(attrs, &mut diag);
+        // the parameter and the initializer only exist if the field does
+        let cfgs = util::cfg_attrs(attrs);
+        if is_phantom {
+            self_args = quote! {
+                #self_args
+                #cfgs #fname: ::core::marker::PhantomData,
+            };
+        } else if is_skipped {
+            // skipped fields are not accepted by the ctor, so use their default value instead
+            let default = if is_const {
+                quote! { <#ty as ::bagel::Constdef>::DEFAULT }
+            } else {
+                quote! { ::core::default::Default::default() }
+            };
+            self_args = quote! {
+                #self_args
+                #cfgs #fname: #default,
+            };
+        } else {
+            // not a phantomdata struct, add it
+            arg_docs.push(match self::doc_text(attrs) {
+                Some(doc) => format!("- `{}`: {}", fname, doc),
+                None => format!("- `{}`", fname),
+            });
+            tokens = quote! {
+                #tokens
+                #cfgs #fname: #ty,
+            };
+            self_args = quote! {
+                #self_args
+                #cfgs #fname,
+            };
         }
-        .into();
+    }
+    let arg_docs = if arg_docs.is_empty() {
+        quote! {}
     } else {
-        // handle extended case: struct with fields
-        let mut tokens = quote! {};
-        let mut self_args = quote! {};
-        for (fname, ty, attrs) in fields {
-            err_if_subattr_on_primary_attr!(
-                "field",
-                // marking const_ctor on a field is invalid
-                ATTR_CONST_CTOR in attrs,
-            );
-            let is_phantom = ok_else_ret!(util::single_instance_of_attr(attrs, util::ATTR_PHANTOM));
-            if !is_phantom {
-                // not a phantomdata struct, add it
-                tokens = quote! {
-                    #tokens
-                    #fname: #ty,
-                };
-                self_args = quote! {
-                    #self_args
-                    #fname,
-                };
-            } else {
-                self_args = quote! {
-                    #self_args
-                    #fname: ::core::marker::PhantomData,
-                };
-            }
+        quote! {
+            #[doc = ""]
+            #[doc = "# Arguments"]
+            #[doc = ""]
+            #(#[doc = #arg_docs])*
         }
-        let tokens = quote! {
-            impl #impl_gen #struct_name #ty_gen #where_clause {
-                #func new(
-                    #tokens
-                ) -> #struct_name #ty_gen {
-                    Self {
-                        #self_args
-                    }
+    };
+    diag.finish(quote! {
+        impl #impl_gen #struct_name #ty_gen #where_clause {
+            #[doc = #summary]
+            #arg_docs
+            #allow_deprecated
+            #method_attrs
+            #func new(
+                #tokens
+            ) -> #struct_name #ty_gen {
+                Self {
+                    #self_args
                 }
             }
-        };
-        tokens.into()
+        }
+    })
+}
+
+/// Returns the doc comments of a field joined into a single line, if it has any
+fn doc_text(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = util::field_docs(attrs)
+        .into_iter()
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(nv)) => match nv.lit {
+                Lit::Str(doc) => Some(doc.value().trim().to_owned()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(" "))
     }
 }
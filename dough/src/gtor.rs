@@ -2,21 +2,104 @@
 //!
 
 use crate::util;
-use crate::util::ATTR_PHANTOM;
+use crate::util::{
+    AtomicOrdering, Diagnostics, DocTemplate, FieldFilter, Method, MethodAttrs, Wrapper,
+    ATTR_BAGEL, ATTR_PHANTOM,
+};
 use proc_macro::TokenStream;
-use quote::{quote, ToTokens};
-use syn::{parse_macro_input, punctuated::Punctuated, DeriveInput, Ident, Meta, Token, Type};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Attribute, DeriveInput, Generics, Ident, Meta, NestedMeta, Type};
 use util::TYCOPY;
 
 /// The attribute for constant (compile-time) getters
 const ATTR_CONST_GTOR: &str = "gtor_const";
 const ATTR_GTOR_COPY: &str = "gtor_copy";
 const ATTR_GTOR_SKIP: &str = "gtor_skip";
+const ATTR_GTOR: &str = "gtor";
+/// The items accepted by the `#[gtor(...)]` attribute on a field (the accessor kinds and `attrs`)
+const GTOR_FIELD_ITEMS: &[&str] = &["get", "get_mut", "into", "clone", "inner", "attrs"];
+/// The items accepted by the `#[gtor(...)]` attribute on the struct
+const GTOR_LIST_ITEMS: &[&str] = &[
+    "get", "get_mut", "into", "clone", "inner", "only", "except", "ordering", "doc", "attrs",
+];
+
+/// The kinds of accessors to generate for a field
+#[derive(Clone, Copy)]
+struct AccessorKinds {
+    /// `get_<field>(&self)`
+    get: bool,
+    /// `get_<field>_mut(&mut self)`
+    get_mut: bool,
+    /// `into_<field>(self)`
+    into: bool,
+    /// `get_<field>_cloned(&self)`
+    clone: bool,
+    /// `lock_<field>(&self)`, `borrow_<field>(&self)` and friends for lock and cell fields
+    inner: bool,
+}
+
+impl AccessorKinds {
+    /// Without an explicit selection, only immutable getters are generated
+    const DEFAULT: Self = Self {
+        get: true,
+        get_mut: false,
+        into: false,
+        clone: false,
+        inner: false,
+    };
+    const NONE: Self = Self {
+        get: false,
+        get_mut: false,
+        into: false,
+        clone: false,
+        inner: false,
+    };
+    /// Select the given kind, returning false if it isn't a known kind
+    fn select(&mut self, kind: &str) -> bool {
+        match kind {
+            "get" => self.get = true,
+            "get_mut" => self.get_mut = true,
+            "into" => self.into = true,
+            "clone" => self.clone = true,
+            "inner" => self.inner = true,
+            _ => return false,
+        }
+        true
+    }
+    /// Report the selected kinds that need references to the field, which a packed struct can't
+    /// provide
+    fn reject_references(&self, attr: &Attribute, diag: &mut Diagnostics) {
+        let selected = [
+            ("get_mut", self.get_mut),
+            ("clone", self.clone),
+            ("inner", self.inner),
+        ];
+        for (kind, _) in selected.iter().filter(|(_, is_selected)| *is_selected) {
+            diag.error(
+                attr,
+                format!(
+                    "`{}` accessors can't be generated for a `#[repr(packed)]` struct, since \
+                    references to its fields may be unaligned",
+                    kind
+                ),
+            );
+        }
+    }
+}
 
 pub(crate) fn derive_gtor(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = parse_macro_input!(input);
-    let struct_name = ast.ident.clone();
+    expand(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut diag = Diagnostics::default();
+    let struct_name = &ast.ident;
     err_if_subattr_on_primary_attr!(
+        diag,
         "entire struct",
         // a struct cannot be entirely phantom
         ATTR_PHANTOM in ast.attrs,
@@ -24,124 +107,510 @@ pub(crate) fn derive_gtor(input: TokenStream) -> TokenStream {
         ATTR_GTOR_COPY in ast.attrs,
         // marking an entire struct to be skipped is useless
         ATTR_GTOR_SKIP in ast.attrs,
+        ATTR_BAGEL in ast.attrs,
     );
 
-    let mut attrlist: ::std::collections::HashSet<String> = Default::default();
-    for attr in &ast.attrs {
-        if attr.path.is_ident("gtor") {
-            attrlist.extend(
-                attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
-                    .unwrap()
-                    .iter()
-                    .map(|v| v.path().into_token_stream().to_string()),
-            );
-            break;
-        }
-    }
-    let needs_get = attrlist.get("get").map(|_| true).unwrap_or(true);
-    let needs_get_mut = attrlist.get("get_mut").map(|_| true).unwrap_or(false);
-
     // get generics
     let (impl_gen, ty_gen, where_clause) = &ast.generics.split_for_impl();
     // get fields
-    let fields = match util::get_struct_field_names(&ast) {
-        Ok(f) => f,
-        Err(e) => return e,
-    };
+    let fields = util::get_struct_field_names(ast)?;
+
+    let mut struct_kinds = None;
+    let mut filter = FieldFilter::default();
+    let mut ordering = None;
+    let mut template = None;
+    // accessors are `#[inline]` (and `#[must_use]` if they return a value), unless other attributes
+    // are given with `attrs(...)`
+    let mut method_attrs = MethodAttrs::Defaults { must_use: true };
+    for item in util::get_attr_list(&ast.attrs, ATTR_GTOR, &mut diag) {
+        if filter.parse(&item, ATTR_GTOR, &fields, &mut diag) {
+            continue;
+        }
+        if let Some(attrs) = MethodAttrs::parse(&item, &mut diag) {
+            method_attrs = attrs;
+            continue;
+        }
+        if let NestedMeta::Meta(Meta::NameValue(nv)) = &item {
+            if nv.path.is_ident("ordering") {
+                // the default memory ordering for atomic fields
+                ordering = AtomicOrdering::parse(&nv.lit, &mut diag);
+                continue;
+            }
+            if nv.path.is_ident("doc") {
+                // a template for the doc comments of every generated method
+                template = DocTemplate::parse(&nv.lit, &mut diag);
+                continue;
+            }
+        }
+        let kind = util::nested_name(&item);
+        let kinds = struct_kinds.get_or_insert(AccessorKinds::NONE);
+        if !kind.map(|kind| kinds.select(&kind)).unwrap_or(false) {
+            util::unknown_nested(&item, ATTR_GTOR, GTOR_LIST_ITEMS, &mut diag);
+        }
+    }
+    let struct_kinds = struct_kinds.unwrap_or(AccessorKinds::DEFAULT);
+    // fields of packed structs may be unaligned, so they can't be accessed by reference
+    let is_packed = util::is_packed(&ast.attrs);
+    if is_packed {
+        if let Some(attr) = ast.attrs.iter().find(|attr| attr.path.is_ident(ATTR_GTOR)) {
+            struct_kinds.reject_references(attr, &mut diag);
+        }
+    }
     // get function header
-    let func = match util::get_func_header(&ast.attrs, ATTR_CONST_GTOR) {
-        Ok(f) => f,
-        Err(e) => return e,
-    };
-    if !fields.is_empty() {
-        let mut q = quote!();
-        for (field, ty, attrs) in fields {
-            err_if_subattr_on_primary_attr!(
-                "field",
-                // marking const_gtor on a field is invalid
-                ATTR_CONST_GTOR in attrs,
-            );
-            let is_phantom = ok_else_ret!(util::single_instance_of_attr(attrs, util::ATTR_PHANTOM));
-            let is_explicitly_copy =
-                ok_else_ret!(util::single_instance_of_attr(attrs, ATTR_GTOR_COPY));
-            let is_skipped = ok_else_ret!(util::single_instance_of_attr(attrs, ATTR_GTOR_SKIP));
-            if (is_skipped && is_explicitly_copy) || (is_explicitly_copy && is_phantom) {
-                // both at once, huh?
-                return syn::Error::new(
-                    field.span(),
-                    "Using `#[gtor_copy]` with `#[gtor_skip]` is invalid",
-                )
-                .into_compile_error()
-                .into();
+    let func = util::get_func_header(&ast.attrs, ATTR_CONST_GTOR, &mut diag);
+    let mut q = quote!();
+    for (field, ty, attrs) in fields {
+        err_if_subattr_on_primary_attr!(
+            diag,
+            "field",
+            // marking const_gtor on a field is invalid
+            ATTR_CONST_GTOR in attrs,
+        );
+        let is_phantom = util::single_instance_of_attr(attrs, ATTR_PHANTOM, &mut diag);
+        let explicit_copy = util::get_attr(attrs, ATTR_GTOR_COPY, &mut diag);
+        let is_explicitly_copy = explicit_copy.is_some();
+        let is_skipped = util::single_instance_of_attr(attrs, ATTR_GTOR_SKIP, &mut diag)
+            | util::is_bagel_skipped(attrs, &mut diag);
+        // a selection on the field overrides the selection on the struct
+        let mut field_kinds = None;
+        let mut field_method_attrs = None;
+        for item in util::get_attr_list(attrs, ATTR_GTOR, &mut diag) {
+            let kind = util::nested_name(&item);
+            if let Some(attrs) = MethodAttrs::parse(&item, &mut diag) {
+                field_method_attrs = Some(attrs);
+                continue;
+            }
+            let kinds = field_kinds.get_or_insert(AccessorKinds::NONE);
+            if !kind.map(|kind| kinds.select(&kind)).unwrap_or(false) {
+                util::unknown_nested(&item, ATTR_GTOR, GTOR_FIELD_ITEMS, &mut diag);
+            }
+        }
+        if is_skipped || is_phantom {
+            for attr in attrs.iter().filter(|attr| attr.path.is_ident(ATTR_GTOR)) {
+                diag.error(attr, "selecting accessors on a skipped field is invalid");
+            }
+        }
+        if let (true, Some(kinds), Some(attr)) = (
+            is_packed && !(is_skipped || is_phantom),
+            field_kinds,
+            attrs.iter().find(|attr| attr.path.is_ident(ATTR_GTOR)),
+        ) {
+            kinds.reject_references(attr, &mut diag);
+        }
+        let mut kinds = field_kinds.unwrap_or(struct_kinds);
+        if is_packed {
+            // these have already been reported
+            kinds.get_mut = false;
+            kinds.clone = false;
+            kinds.inner = false;
+        }
+        if let Some(copy_attr) = explicit_copy {
+            // both at once, huh?
+            if is_skipped {
+                diag.error(
+                    copy_attr,
+                    "using `#[gtor_copy]` on a skipped field is invalid",
+                );
+            }
+            if is_phantom {
+                diag.error(
+                    copy_attr,
+                    "using `#[gtor_copy]` with `#[phantom]` is invalid",
+                );
+            }
+        }
+        if !(is_skipped || is_phantom) && filter.includes(field) {
+            // not skipped, not phantom and not filtered out, so add gtor. the methods for this field are
+            // generated separately, so that the field's attributes can be carried over to them
+            let mut methods = Vec::new();
+            let is_prim = self::is_copy_type(ty);
+            let field_name_str = field.to_string();
+            let wrapper = util::wrapper(ty);
+            match (kinds.inner, &wrapper) {
+                (true, Some(wrapper)) => methods.extend(self::wrapper_accessors(
+                    field,
+                    wrapper,
+                    is_explicitly_copy,
+                    &ast.generics,
+                    struct_name,
+                )),
+                // selecting inner accessors for the whole struct only affects lock and cell fields
+                (true, None) if field_kinds.is_some() => diag.error(
+                    ty,
+                    "inner accessors can only be generated for `Mutex`, `RwLock`, `Cell` and \
+                    `RefCell` fields",
+                ),
+                _ => {}
             }
-            if !(is_skipped && is_phantom) {
-                // not skipped and not phantom, so add gtor
-                let is_prim = match &ty {
-                    Type::Path(t) => {
-                        let type_str = t.clone().into_token_stream().to_string();
-                        TYCOPY.contains(type_str.as_str())
+            // `Cell` accessors already include a `get_<field>` if the contents are `Copy`
+            let is_cell_with_inner = match &wrapper {
+                Some(Wrapper::Cell(inner)) => {
+                    kinds.inner && self::is_cell_copy(inner, is_explicitly_copy, &ast.generics)
+                }
+                _ => false,
+            };
+
+            if kinds.get && is_packed {
+                // copy the value out, since a reference may be unaligned
+                let fname = format!("get_{field}", field = field_name_str);
+                let doc_comment = format!(
+                    "Returns the value for the `{field}` field in struct [`{struct_name}`]",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+                let (bound, check) = util::packed_check(ty, &ast.generics);
+                methods.push(Method::returning(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        #func #fname(&self) -> #ty where #bound {
+                            #check
+                            self.#field
+                        }
+                    },
+                ));
+            } else if let (true, Some(atomic)) = (kinds.get, util::atomic(ty)) {
+                // atomics are read with a load instead of being exposed by reference
+                let fname = format!("load_{field}", field = field_name_str);
+                let doc_comment = format!(
+                    "Loads the value of the atomic `{field}` field in struct [`{struct_name}`]",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+                let value = atomic.value;
+                let load = match ordering {
+                    Some(ordering) => {
+                        let ordering = ordering.for_load();
+                        quote! {
+                            pub fn #fname(&self) -> #value {
+                                self.#field.load(#ordering)
+                            }
+                        }
                     }
-                    // all these are copy type (fnptrs, ptrs, refs); no point in returning another ref
-                    Type::BareFn(_) | Type::Never(_) | Type::Ptr(_) | Type::Reference(_) => true,
-                    _ => false,
+                    None => quote! {
+                        pub fn #fname(&self, order: ::core::sync::atomic::Ordering) -> #value {
+                            self.#field.load(order)
+                        }
+                    },
                 };
-                let field_name_str = field.to_string();
-
-                if needs_get {
-                    let mut fname = "get_".to_owned();
-                    fname.push_str(&field_name_str);
-                    let doc_comment = format!(
-                        "Returns the value for the `{field}` field in struct [`{struct_name}`]",
-                        struct_name = struct_name,
-                        field = field_name_str
-                    );
-                    let fname = Ident::new(&fname, field.span());
-
-                    if is_prim || is_explicitly_copy {
-                        // a copy-able type
-                        q = quote! {
-                            #q
-                            #[doc = #doc_comment]
-                            #func #fname(&self) -> #ty {
-                                self.#field
-                            }
-                        };
-                    } else {
-                        q = quote! {
-                            #q
-                            #[doc = #doc_comment]
-                            #func #fname(&self) -> &#ty {
-                                &self.#field
-                            }
-                        };
+                methods.push(Method::returning(&fname, doc_comment, load));
+            } else if kinds.get && !is_cell_with_inner {
+                let mut fname = "get_".to_owned();
+                fname.push_str(&field_name_str);
+                let doc_comment = format!(
+                    "Returns the value for the `{field}` field in struct [`{struct_name}`]",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+
+                let get = if is_prim || is_explicitly_copy {
+                    // a copy-able type
+                    quote! {
+                        #func #fname(&self) -> #ty {
+                            self.#field
+                        }
                     }
-                }
-                if needs_get_mut {
-                    let fname = format!("get_{field}_mut", field = field_name_str);
-                    let doc_comment = format!(
-                        "Returns a mutable reference to the `{field}` field in struct [`{struct_name}`]",
-                        struct_name = struct_name,
-                        field = field_name_str
-                    );
-                    let fname = Ident::new(&fname, field.span());
-
-                    q = quote! {
-                        #q
-                        #[doc = #doc_comment]
-                        pub fn #fname(&mut self) -> &mut #ty {
+                } else {
+                    quote! {
+                        #func #fname(&self) -> &#ty {
+                            &self.#field
+                        }
+                    }
+                };
+                methods.push(Method::returning(&fname, doc_comment, get));
+            }
+            if kinds.get_mut {
+                let fname = format!("get_{field}_mut", field = field_name_str);
+                let doc_comment = format!(
+                    "Returns a mutable reference to the `{field}` field in struct [`{struct_name}`]",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+
+                methods.push(Method::returning(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        #func #fname(&mut self) -> &mut #ty {
                             &mut self.#field
                         }
-                    };
-                }
+                    },
+                ));
             }
-        }
-        q = quote! {
-            impl #impl_gen #struct_name #ty_gen #where_clause {
+            if kinds.into {
+                let fname = format!("into_{field}", field = field_name_str);
+                let doc_comment = format!(
+                    "Consumes the struct [`{struct_name}`], returning the value of the `{field}` field",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+
+                methods.push(Method::returning(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        pub fn #fname(self) -> #ty {
+                            self.#field
+                        }
+                    },
+                ));
+            }
+            if kinds.clone {
+                let fname = format!("get_{field}_cloned", field = field_name_str);
+                let doc_comment = format!(
+                    "Returns a clone of the `{field}` field in struct [`{struct_name}`]",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+                // a bound on a concrete type is checked with the struct, so a field that isn't
+                // `Clone` is reported at the field instead
+                let (bound, clone) = if util::mentions_type_param(ty, &ast.generics) {
+                    (
+                        quote! { #ty: ::core::clone::Clone },
+                        quote! { ::core::clone::Clone::clone(&self.#field) },
+                    )
+                } else {
+                    (
+                        quote_spanned! {ty.span()=> #ty: ::bagel::__private::CloneField },
+                        quote! { ::bagel::__private::CloneField::clone_field(&self.#field) },
+                    )
+                };
+
+                methods.push(Method::returning(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        pub fn #fname(&self) -> #ty where #bound {
+                            #clone
+                        }
+                    },
+                ));
+            }
+            let methods = util::finish_methods(
+                methods,
+                field,
+                attrs,
+                template.as_ref(),
+                field_method_attrs.as_ref().unwrap_or(&method_attrs),
+                struct_name,
+            );
+            q = quote! {
                 #q
+                #methods
+            };
+        }
+    }
+    diag.finish(quote! {
+        impl #impl_gen #struct_name #ty_gen #where_clause {
+            #q
+        }
+    })
+}
+
+/// Returns the `Cell` fields that get a `set_<field>` from the `inner` accessors, so that `Stor` can
+/// leave its own (conflicting) `set_<field>` out. The errors in the attributes are reported by
+/// `Gtor` itself, so they are ignored here
+pub(crate) fn inner_cell_setters(ast: &DeriveInput) -> Vec<&Ident> {
+    let mut ignored = Diagnostics::default();
+    let fields = match util::get_struct_field_names(ast) {
+        Ok(fields) => fields,
+        Err(_) => return Vec::new(),
+    };
+    if util::is_packed(&ast.attrs) {
+        return Vec::new();
+    }
+    // only the known kinds make a selection, like in `expand`
+    let select = |selection: &mut Option<AccessorKinds>, item: &NestedMeta| {
+        let mut kinds = selection.unwrap_or(AccessorKinds::NONE);
+        if util::nested_name(item)
+            .map(|kind| kinds.select(&kind))
+            .unwrap_or(false)
+        {
+            *selection = Some(kinds);
+        }
+    };
+    let mut filter = FieldFilter::default();
+    let mut struct_kinds = None;
+    for item in util::get_attr_list(&ast.attrs, ATTR_GTOR, &mut ignored) {
+        if !filter.parse(&item, ATTR_GTOR, &fields, &mut ignored) {
+            select(&mut struct_kinds, &item);
+        }
+    }
+    let struct_kinds = struct_kinds.unwrap_or(AccessorKinds::DEFAULT);
+    fields
+        .iter()
+        .filter(|(field, ty, attrs)| {
+            let mut field_kinds = None;
+            for item in util::get_attr_list(attrs, ATTR_GTOR, &mut ignored) {
+                select(&mut field_kinds, &item);
+            }
+            let is_skipped = attrs
+                .iter()
+                .any(|attr| attr.path.is_ident(ATTR_GTOR_SKIP) || attr.path.is_ident(ATTR_PHANTOM))
+                || util::is_bagel_skipped(attrs, &mut ignored);
+            field_kinds.unwrap_or(struct_kinds).inner
+                && !is_skipped
+                && filter.includes(field)
+                && matches!(util::wrapper(ty), Some(Wrapper::Cell(_)))
+        })
+        .map(|(field, _, _)| *field)
+        .collect()
+}
+
+/// Returns true if the type is known to be `Copy`
+fn is_copy_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(t) => {
+            let type_str = t.clone().into_token_stream().to_string();
+            TYCOPY.contains(type_str.as_str())
+        }
+        // all these are copy type (fnptrs, ptrs, refs); no point in returning another ref
+        Type::BareFn(_) | Type::Never(_) | Type::Ptr(_) | Type::Reference(_) => true,
+        _ => false,
+    }
+}
+
+/// Returns true if the contents of a `Cell` field can be copied out with `Cell::get`. That needs
+/// `Copy` contents, and a bound on a concrete type that isn't `Copy` is an error, so the bound is
+/// only used for generic parameters
+fn is_cell_copy(inner: &Type, is_explicitly_copy: bool, generics: &Generics) -> bool {
+    let is_generic = generics.type_params().any(
+        |param| matches!(inner, Type::Path(t) if t.qself.is_none() && t.path.is_ident(&param.ident)),
+    );
+    self::is_copy_type(inner) || is_explicitly_copy || is_generic
+}
+
+/// Generates the accessors for the contents of a lock or cell field:
+/// - `Mutex`: `lock_<field>`
+/// - `RwLock`: `read_<field>` and `write_<field>`
+/// - `Cell`: `get_<field>` (if the contents are `Copy`) and `set_<field>` (which replaces the
+///   setter of `Stor`, see [`inner_cell_setters`])
+/// - `RefCell`: `borrow_<field>` and `borrow_<field>_mut`
+fn wrapper_accessors(
+    field: &Ident,
+    wrapper: &Wrapper<'_>,
+    is_explicitly_copy: bool,
+    generics: &Generics,
+    struct_name: &Ident,
+) -> Vec<Method> {
+    let method = |prefix: &str, suffix: &str| {
+        Ident::new(&format!("{}{}{}", prefix, field, suffix), field.span())
+    };
+    let doc = |what: &str| {
+        format!(
+            "{} the `{}` field in struct [`{}`]",
+            what, field, struct_name
+        )
+    };
+    match wrapper {
+        Wrapper::Mutex(inner) => {
+            let lock_fn = method("lock_", "");
+            let lock_doc = doc("Locks the mutex in");
+            vec![Method::returning(
+                &lock_fn,
+                lock_doc,
+                quote! {
+                    pub fn #lock_fn(
+                        &self,
+                    ) -> ::std::sync::LockResult<::std::sync::MutexGuard<'_, #inner>> {
+                        self.#field.lock()
+                    }
+                },
+            )]
+        }
+        Wrapper::RwLock(inner) => {
+            let (read_fn, write_fn) = (method("read_", ""), method("write_", ""));
+            let (read_doc, write_doc) = (
+                doc("Locks (with shared read access) the lock in"),
+                doc("Locks (with exclusive write access) the lock in"),
+            );
+            vec![
+                Method::returning(
+                    &read_fn,
+                    read_doc,
+                    quote! {
+                        pub fn #read_fn(
+                            &self,
+                        ) -> ::std::sync::LockResult<::std::sync::RwLockReadGuard<'_, #inner>> {
+                            self.#field.read()
+                        }
+                    },
+                ),
+                Method::returning(
+                    &write_fn,
+                    write_doc,
+                    quote! {
+                        pub fn #write_fn(
+                            &self,
+                        ) -> ::std::sync::LockResult<::std::sync::RwLockWriteGuard<'_, #inner>> {
+                            self.#field.write()
+                        }
+                    },
+                ),
+            ]
+        }
+        Wrapper::Cell(inner) => {
+            let (get_fn, set_fn) = (method("get_", ""), method("set_", ""));
+            let (get_doc, set_doc) = (
+                doc("Returns a copy of the contents of the cell in"),
+                doc("Sets the contents of the cell in"),
+            );
+            let set = Method::unit(
+                &set_fn,
+                set_doc,
+                quote! {
+                    pub fn #set_fn(&self, value: #inner) {
+                        self.#field.set(value)
+                    }
+                },
+            );
+            if self::is_cell_copy(inner, is_explicitly_copy, generics) {
+                let get = Method::returning(
+                    &get_fn,
+                    get_doc,
+                    quote! {
+                        pub fn #get_fn(&self) -> #inner where #inner: ::core::marker::Copy {
+                            self.#field.get()
+                        }
+                    },
+                );
+                vec![get, set]
+            } else {
+                vec![set]
             }
-        };
-        q.into()
-    } else {
-        return "".parse().unwrap();
+        }
+        Wrapper::RefCell(inner) => {
+            let (borrow_fn, borrow_mut_fn) = (method("borrow_", ""), method("borrow_", "_mut"));
+            let (borrow_doc, borrow_mut_doc) = (
+                doc("Immutably borrows the contents of the cell in"),
+                doc("Mutably borrows the contents of the cell in"),
+            );
+            vec![
+                Method::returning(
+                    &borrow_fn,
+                    borrow_doc,
+                    quote! {
+                        pub fn #borrow_fn(&self) -> ::core::cell::Ref<'_, #inner> {
+                            self.#field.borrow()
+                        }
+                    },
+                ),
+                Method::returning(
+                    &borrow_mut_fn,
+                    borrow_mut_doc,
+                    quote! {
+                        pub fn #borrow_mut_fn(&self) -> ::core::cell::RefMut<'_, #inner> {
+                            self.#field.borrow_mut()
+                        }
+                    },
+                ),
+            ]
+        }
     }
 }
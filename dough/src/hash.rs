@@ -0,0 +1,185 @@
+//! This module is responsible for the `hash!` macro, which hashes a literal in the compiler. The
+//! algorithms mirror the `const fn`s in `bagel::hash`, which the tests of `bagel` compare them
+//! against
+//!
+
+use crate::util;
+use ::proc_macro::TokenStream;
+use ::proc_macro2::{Literal, Span};
+use ::quote::quote;
+use ::std::convert::TryInto;
+use ::syn::parse::{Parse, ParseStream};
+use ::syn::{parse_macro_input, Ident, Lit, Token};
+
+/// The algorithms accepted by `hash!(<algorithm>, ...)`
+const ALGORITHMS: &[&str] = &["fnv1a_32", "fnv1a_64", "xxh64", "crc32", "crc32c"];
+
+/// The input of `hash!`: an optional algorithm, followed by a string or byte string literal
+struct HashInput {
+    algorithm: Option<Ident>,
+    lit: Lit,
+}
+
+impl Parse for HashInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let algorithm = if input.peek(Ident) {
+            let algorithm = input.parse()?;
+            input.parse::<Token![,]>()?;
+            Some(algorithm)
+        } else {
+            None
+        };
+        let lit = input.parse()?;
+        let _ = input.parse::<Option<Token![,]>>()?;
+        Ok(Self { algorithm, lit })
+    }
+}
+
+pub(crate) fn hash(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as HashInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: HashInput) -> syn::Result<::proc_macro2::TokenStream> {
+    let bytes = match &input.lit {
+        Lit::Str(lit) => lit.value().into_bytes(),
+        Lit::ByteStr(lit) => lit.value(),
+        lit => {
+            return Err(syn::Error::new_spanned(
+                lit,
+                "expected a string or byte string literal",
+            ))
+        }
+    };
+    let (algorithm, span) = match &input.algorithm {
+        Some(algorithm) => (algorithm.to_string(), algorithm.span()),
+        None => ("fnv1a_64".to_owned(), Span::call_site()),
+    };
+    let mut hash = match algorithm.as_str() {
+        "fnv1a_32" => Literal::u32_suffixed(self::fnv1a_32(&bytes)),
+        "fnv1a_64" => Literal::u64_suffixed(self::fnv1a_64(&bytes)),
+        "xxh64" => Literal::u64_suffixed(self::xxh64(&bytes)),
+        "crc32" => Literal::u32_suffixed(self::crc32(&bytes, CRC32_POLY)),
+        "crc32c" => Literal::u32_suffixed(self::crc32(&bytes, CRC32C_POLY)),
+        _ => {
+            let message = format!("unknown hash algorithm `{}`", algorithm);
+            let help = match util::suggest(&algorithm, ALGORITHMS) {
+                Some(suggestion) => format!("did you mean `{}`?", suggestion),
+                None => format!("expected one of {}", ALGORITHMS.join(", ")),
+            };
+            return Err(syn::Error::new(
+                span,
+                format!("{}\n= help: {}", message, help),
+            ));
+        }
+    };
+    hash.set_span(input.lit.span());
+    Ok(quote! { #hash })
+}
+
+fn fnv1a_32(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+const XXH_PRIME64_1: u64 = 0x9e37_79b1_85eb_ca87;
+const XXH_PRIME64_2: u64 = 0xc2b2_ae3d_27d4_eb4f;
+const XXH_PRIME64_3: u64 = 0x1656_67b1_9e37_79f9;
+const XXH_PRIME64_4: u64 = 0x85eb_ca77_c2b2_ae63;
+const XXH_PRIME64_5: u64 = 0x27d4_eb2f_1656_67c5;
+
+fn xxh64_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(XXH_PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(XXH_PRIME64_1)
+}
+
+fn xxh64_merge(acc: u64, value: u64) -> u64 {
+    (acc ^ self::xxh64_round(0, value))
+        .wrapping_mul(XXH_PRIME64_1)
+        .wrapping_add(XXH_PRIME64_4)
+}
+
+/// The 64-bit xxHash, with a seed of `0`
+fn xxh64(bytes: &[u8]) -> u64 {
+    let read_u64 = |chunk: &[u8]| u64::from_le_bytes(chunk[..8].try_into().unwrap());
+    let mut rest = bytes;
+    let mut hash = if bytes.len() >= 32 {
+        let mut v = [
+            XXH_PRIME64_1.wrapping_add(XXH_PRIME64_2),
+            XXH_PRIME64_2,
+            0,
+            XXH_PRIME64_1.wrapping_neg(),
+        ];
+        while rest.len() >= 32 {
+            for (lane, chunk) in v.iter_mut().zip(rest.chunks_exact(8)) {
+                *lane = self::xxh64_round(*lane, read_u64(chunk));
+            }
+            rest = &rest[32..];
+        }
+        let hash = v[0]
+            .rotate_left(1)
+            .wrapping_add(v[1].rotate_left(7))
+            .wrapping_add(v[2].rotate_left(12))
+            .wrapping_add(v[3].rotate_left(18));
+        v.iter()
+            .fold(hash, |hash, lane| self::xxh64_merge(hash, *lane))
+    } else {
+        XXH_PRIME64_5
+    };
+    hash = hash.wrapping_add(bytes.len() as u64);
+    while rest.len() >= 8 {
+        hash ^= self::xxh64_round(0, read_u64(rest));
+        hash = hash
+            .rotate_left(27)
+            .wrapping_mul(XXH_PRIME64_1)
+            .wrapping_add(XXH_PRIME64_4);
+        rest = &rest[8..];
+    }
+    if rest.len() >= 4 {
+        let word = u32::from_le_bytes(rest[..4].try_into().unwrap());
+        hash ^= (word as u64).wrapping_mul(XXH_PRIME64_1);
+        hash = hash
+            .rotate_left(23)
+            .wrapping_mul(XXH_PRIME64_2)
+            .wrapping_add(XXH_PRIME64_3);
+        rest = &rest[4..];
+    }
+    for byte in rest {
+        hash ^= (*byte as u64).wrapping_mul(XXH_PRIME64_5);
+        hash = hash.rotate_left(11).wrapping_mul(XXH_PRIME64_1);
+    }
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(XXH_PRIME64_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(XXH_PRIME64_3);
+    hash ^ (hash >> 32)
+}
+
+const CRC32_POLY: u32 = 0xedb8_8320;
+const CRC32C_POLY: u32 = 0x82f6_3b78;
+
+/// A bitwise CRC-32 with the given reversed polynomial
+fn crc32(bytes: &[u8], poly: u32) -> u32 {
+    let crc = bytes.iter().fold(!0u32, |mut crc, byte| {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+        }
+        crc
+    });
+    !crc
+}
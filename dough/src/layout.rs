@@ -0,0 +1,145 @@
+//! This module is responsible for the `#[layout(...)]` attribute, which checks the size, alignment
+//! and field offsets of a struct at compile-time
+//!
+
+use crate::util::{self, Diagnostics};
+use ::proc_macro::TokenStream;
+use ::proc_macro2::Literal;
+use ::quote::{format_ident, quote, quote_spanned, ToTokens};
+use ::syn::ext::IdentExt;
+use ::syn::{
+    parse_macro_input, AttributeArgs, Data, DeriveInput, Index, Lit, Member, Meta, NestedMeta,
+};
+
+const ATTR_LAYOUT: &str = "layout";
+/// The items accepted by `#[layout(...)]` on the struct
+const LAYOUT_LIST_ITEMS: &[&str] = &["size", "align"];
+/// The items accepted by `#[layout(...)]` on a field
+const LAYOUT_FIELD_ITEMS: &[&str] = &["offset"];
+
+pub(crate) fn layout(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let mut ast = parse_macro_input!(input as DeriveInput);
+    expand(args, &mut ast)
+        .unwrap_or_else(|e| {
+            // keep the struct around, so that its uses don't report errors of their own
+            let errors = e.into_compile_error();
+            quote! { #ast #errors }
+        })
+        .into()
+}
+
+/// Returns the value of a `name = <integer>` item, as an unsuffixed `usize` literal spanned at
+/// the given value
+fn parse_value(item: &NestedMeta, diag: &mut Diagnostics) -> Option<Literal> {
+    let lit = match item {
+        NestedMeta::Meta(Meta::NameValue(nv)) => &nv.lit,
+        item => {
+            diag.error(item, "expected an item of the form `name = <integer>`");
+            return None;
+        }
+    };
+    match lit {
+        Lit::Int(int) => match int.base10_parse::<usize>() {
+            Ok(value) => {
+                let mut value = Literal::usize_unsuffixed(value);
+                value.set_span(int.span());
+                Some(value)
+            }
+            Err(e) => {
+                diag.push(e);
+                None
+            }
+        },
+        lit => {
+            diag.error(lit, "expected an integer literal");
+            None
+        }
+    }
+}
+
+/// Returns a check that fails the build if `actual` isn't `expected`. The compiler reports both
+/// values in the error, at the span of `expected`
+fn check(expected: &Literal, actual: ::proc_macro2::TokenStream) -> ::proc_macro2::TokenStream {
+    quote_spanned! {expected.span()=>
+        const _: [(); #expected] = [(); #actual];
+    }
+}
+
+fn expand(args: AttributeArgs, ast: &mut DeriveInput) -> syn::Result<::proc_macro2::TokenStream> {
+    let mut diag = Diagnostics::default();
+    let name = ast.ident.clone();
+    let mut checks = quote! {};
+    for item in args {
+        let actual = match util::nested_name(&item).as_deref() {
+            Some("size") => quote! { ::core::mem::size_of::<#name>() },
+            Some("align") => quote! { ::core::mem::align_of::<#name>() },
+            _ => {
+                util::unknown_nested(&item, ATTR_LAYOUT, LAYOUT_LIST_ITEMS, &mut diag);
+                continue;
+            }
+        };
+        if let Some(expected) = self::parse_value(&item, &mut diag) {
+            let check = self::check(&expected, actual);
+            checks = quote! { #checks #check };
+        }
+    }
+    let fields = match &mut ast.data {
+        Data::Struct(data) => &mut data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &ast.ident,
+                "`layout` can only be used on structs",
+            ))
+        }
+    };
+    let mut offsets = quote! {};
+    for (i, field) in fields.iter_mut().enumerate() {
+        let (member, const_name) = match &field.ident {
+            Some(ident) => (
+                Member::Named(ident.clone()),
+                format_ident!("OFFSET_{}", ident.unraw().to_string().to_uppercase()),
+            ),
+            None => (
+                Member::Unnamed(Index::from(i)),
+                format_ident!("OFFSET_{}", i),
+            ),
+        };
+        let actual = quote! { ::core::mem::offset_of!(#name, #member) };
+        let cfgs = util::cfg_attrs(&field.attrs);
+        for item in util::get_attr_list(&field.attrs, ATTR_LAYOUT, &mut diag) {
+            match util::nested_name(&item).as_deref() {
+                Some("offset") => {
+                    if let Some(expected) = self::parse_value(&item, &mut diag) {
+                        let check = self::check(&expected, actual.clone());
+                        checks = quote! { #checks #cfgs #check };
+                    }
+                }
+                _ => util::unknown_nested(&item, ATTR_LAYOUT, LAYOUT_FIELD_ITEMS, &mut diag),
+            }
+        }
+        // the field attributes belong to this macro, and would be rejected by the compiler
+        field.attrs.retain(|attr| !attr.path.is_ident(ATTR_LAYOUT));
+        let vis = &field.vis;
+        let doc = format!("The offset of `{}` in bytes", member.to_token_stream());
+        offsets = quote! {
+            #offsets
+            #cfgs
+            #[doc = #doc]
+            #vis const #const_name: usize = #actual;
+        };
+    }
+    if !ast.generics.params.is_empty() {
+        diag.error(
+            &ast.generics,
+            "`layout` is not supported on generic structs",
+        );
+    }
+    diag.finish(quote! {
+        #ast
+        impl #name {
+            #offsets
+        }
+        #checks
+    })
+}
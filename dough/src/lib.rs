@@ -10,28 +10,61 @@ use proc_macro::TokenStream;
 
 #[macro_use]
 mod macros;
+mod const_format;
 mod constdef;
 mod ctor;
 mod gtor;
+mod hash;
+mod layout;
+mod static_map;
 mod stor;
 mod util;
+mod zeroable;
 
-#[proc_macro_derive(Ctor, attributes(ctor_const, phantom))]
+#[proc_macro_derive(Ctor, attributes(ctor_const, ctor, phantom, bagel))]
 pub fn derive_ctor(input: TokenStream) -> TokenStream {
     ctor::derive_ctor(input)
 }
 
-#[proc_macro_derive(Gtor, attributes(gtor_const, gtor_copy, gtor_skip, phantom, gtor))]
+#[proc_macro_derive(
+    Gtor,
+    attributes(gtor_const, gtor_copy, gtor_skip, phantom, gtor, bagel)
+)]
 pub fn derive_gtor(input: TokenStream) -> TokenStream {
     gtor::derive_gtor(input)
 }
 
-#[proc_macro_derive(Stor, attributes(stor_skip, phantom))]
+#[proc_macro_derive(Stor, attributes(stor_const, stor_skip, phantom, stor, bagel))]
 pub fn derive_stor(input: TokenStream) -> TokenStream {
     stor::derive_stor(input)
 }
 
-#[proc_macro_derive(Constdef)]
+#[proc_macro_derive(Constdef, attributes(constdef))]
 pub fn derive_constdef(input: TokenStream) -> TokenStream {
     constdef::derive(input)
 }
+
+#[proc_macro_derive(Zeroable)]
+pub fn derive_zeroable(input: TokenStream) -> TokenStream {
+    zeroable::derive_zeroable(input)
+}
+
+#[proc_macro_attribute]
+pub fn layout(args: TokenStream, input: TokenStream) -> TokenStream {
+    layout::layout(args, input)
+}
+
+#[proc_macro]
+pub fn hash(input: TokenStream) -> TokenStream {
+    hash::hash(input)
+}
+
+#[proc_macro]
+pub fn static_map(input: TokenStream) -> TokenStream {
+    static_map::static_map(input)
+}
+
+#[proc_macro]
+pub fn const_format(input: TokenStream) -> TokenStream {
+    const_format::const_format(input)
+}
@@ -16,24 +16,13 @@ macro_rules! gen_typeset {
     };
 }
 
-macro_rules! ok_else_ret {
-    ($e:expr) => {
-        match $e {
-            Ok(v) => v,
-            Err(e) => return e,
-        }
-    };
-}
-
 macro_rules! err_if_subattr_on_primary_attr {
-    ($callpos:literal, $($attr:ident in $attrs:expr),* $(,)*) => {
-        $(if ok_else_ret!($crate::util::single_instance_of_attr(&$attrs, $attr)) {
-            return ::syn::Error::new(
-                ::syn::spanned::Spanned::span($attrs.last().unwrap()),
-                format!("Error: Marking sub-attribute `{}` on the {} is invalid!", $attr, $callpos),
-            )
-            .into_compile_error()
-            .into();
+    ($diag:expr, $callpos:literal, $($attr:ident in $attrs:expr),* $(,)*) => {
+        $(for attr in $attrs.iter().filter(|attr| attr.path.is_ident($attr)) {
+            $diag.error(
+                attr,
+                format!("marking sub-attribute `{}` on the {} is invalid", $attr, $callpos),
+            );
         })*
     };
 }
@@ -0,0 +1,302 @@
+//! This module is responsible for the `static_map!` macro, which builds a `bagel::map::StaticMap`
+//! with a perfect hash function for the given keys. The hash function uses the "hash, displace
+//! and compress" (CHD) algorithm: every key is hashed to a bucket, and every bucket is given a pair
+//! of displacements that send all of its keys to free slots. The hashing and the slot computation
+//! must match the ones in `bagel::map`
+//!
+
+use crate::util;
+use ::proc_macro::TokenStream;
+use ::quote::quote;
+use ::std::collections::HashMap;
+use ::syn::parse::{Parse, ParseStream};
+use ::syn::punctuated::Punctuated;
+use ::syn::{parse_macro_input, Attribute, Expr, ExprLit, ExprUnary, Lit, Token, UnOp};
+
+const ATTR_IGNORE_ASCII_CASE: &str = "ignore_ascii_case";
+/// The average number of keys in a bucket
+const BUCKET_SIZE: usize = 5;
+/// The number of seeds that are tried before giving up (which is practically unreachable)
+const MAX_SEEDS: u64 = 1 << 16;
+
+struct Entry {
+    key: Expr,
+    value: Expr,
+}
+
+impl Parse for Entry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let value = input.parse()?;
+        Ok(Self { key, value })
+    }
+}
+
+struct MapInput {
+    attrs: Vec<Attribute>,
+    entries: Punctuated<Entry, Token![,]>,
+}
+
+impl Parse for MapInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            attrs: input.call(Attribute::parse_inner)?,
+            entries: input.parse_terminated(Entry::parse)?,
+        })
+    }
+}
+
+/// The kind of the keys of a map, which decides the type of the keys
+#[derive(Clone, Copy, PartialEq)]
+enum KeyKind {
+    Str,
+    ByteStr,
+    Int,
+}
+
+impl KeyKind {
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Str => "a string literal",
+            Self::ByteStr => "a byte string literal",
+            Self::Int => "an integer literal",
+        }
+    }
+}
+
+/// Returns the kind of the key, and the bytes that it is hashed as
+fn parse_key(key: &Expr) -> syn::Result<(KeyKind, Vec<u8>)> {
+    match key {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => Ok((KeyKind::Str, lit.value().into_bytes())),
+        Expr::Lit(ExprLit {
+            lit: Lit::ByteStr(lit),
+            ..
+        }) => Ok((KeyKind::ByteStr, lit.value())),
+        // integers are hashed as their sign-extended 128-bit value, whatever their type is
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => Ok((
+            KeyKind::Int,
+            lit.base10_parse::<u128>()?.to_le_bytes().to_vec(),
+        )),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => match &**expr {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(lit), ..
+            }) => Ok((
+                KeyKind::Int,
+                lit.base10_parse::<u128>()?
+                    .wrapping_neg()
+                    .to_le_bytes()
+                    .to_vec(),
+            )),
+            _ => Err(self::unsupported_key(key)),
+        },
+        _ => Err(self::unsupported_key(key)),
+    }
+}
+
+fn unsupported_key(key: &Expr) -> syn::Error {
+    syn::Error::new_spanned(
+        key,
+        "expected a string, byte string or integer literal as the key",
+    )
+}
+
+fn hash_bytes(bytes: &[u8], seed: u64) -> u64 {
+    let hash = bytes
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325 ^ seed, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+    self::mix(hash)
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The perfect hash function for a set of keys
+struct PerfectHash {
+    seed: u64,
+    displacements: Vec<(u32, u32)>,
+    /// The index of the key that is in every slot
+    slots: Vec<usize>,
+}
+
+/// Tries to find displacements for every bucket with the given seed
+fn try_seed(keys: &[Vec<u8>], seed: u64) -> Option<PerfectHash> {
+    let len = keys.len() as u32;
+    let buckets_len = keys.len().div_ceil(BUCKET_SIZE);
+    let hashes: Vec<(u32, u32, u32)> = keys
+        .iter()
+        .map(|key| {
+            let hash = self::hash_bytes(key, seed);
+            ((hash >> 32) as u32, hash as u32, self::mix(hash) as u32)
+        })
+        .collect();
+    let mut buckets = vec![Vec::new(); buckets_len];
+    for (i, (g, _, _)) in hashes.iter().enumerate() {
+        buckets[(g % buckets_len as u32) as usize].push(i);
+    }
+    // the largest buckets are the hardest to place, so they go first
+    let mut order: Vec<usize> = (0..buckets_len).collect();
+    order.sort_by_key(|bucket| std::cmp::Reverse(buckets[*bucket].len()));
+    let mut displacements = vec![(0, 0); buckets_len];
+    let mut slots: Vec<Option<usize>> = vec![None; keys.len()];
+    let mut taken = Vec::new();
+    for bucket in order {
+        let placed = (0..len)
+            .flat_map(|d1| (0..len).map(move |d2| (d1, d2)))
+            .find(|&(d1, d2)| {
+                taken.clear();
+                for key in &buckets[bucket] {
+                    let (_, f1, f2) = hashes[*key];
+                    let slot =
+                        (d2.wrapping_add(f1.wrapping_mul(d1)).wrapping_add(f2) % len) as usize;
+                    if slots[slot].is_some() || taken.contains(&slot) {
+                        return false;
+                    }
+                    taken.push(slot);
+                }
+                true
+            });
+        let (d1, d2) = placed?;
+        displacements[bucket] = (d1, d2);
+        for (key, slot) in buckets[bucket].iter().zip(&taken) {
+            slots[*slot] = Some(*key);
+        }
+    }
+    Some(PerfectHash {
+        seed,
+        displacements,
+        slots: slots.into_iter().map(Option::unwrap).collect(),
+    })
+}
+
+pub(crate) fn static_map(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as MapInput);
+    expand(input)
+        .unwrap_or_else(|e| {
+            // the macro is an expression, so every error must be in a block
+            let errors = e.into_compile_error();
+            quote! {{ #errors }}
+        })
+        .into()
+}
+
+fn expand(input: MapInput) -> syn::Result<::proc_macro2::TokenStream> {
+    let mut diag = util::Diagnostics::default();
+    let mut ignore_ascii_case = false;
+    for attr in &input.attrs {
+        if attr.path.is_ident(ATTR_IGNORE_ASCII_CASE) && attr.tokens.is_empty() {
+            ignore_ascii_case = true;
+        } else {
+            let name = attr.path.get_ident().map(ToString::to_string);
+            match name.and_then(|name| util::suggest(&name, &[ATTR_IGNORE_ASCII_CASE])) {
+                Some(suggestion) => diag.error_with_help(
+                    attr,
+                    "unknown attribute",
+                    format!("did you mean `#![{}]`?", suggestion),
+                ),
+                None => diag.error(attr, "expected `#![ignore_ascii_case]`"),
+            }
+        }
+    }
+    let entries: Vec<Entry> = input.entries.into_iter().collect();
+    let mut kind = None;
+    let mut keys = Vec::new();
+    // the first instance of every key, to report duplicates
+    let mut seen: HashMap<Vec<u8>, &Expr> = HashMap::new();
+    for entry in &entries {
+        let (key_kind, mut bytes) = match diag.ok(self::parse_key(&entry.key)) {
+            Some(key) => key,
+            None => continue,
+        };
+        match kind {
+            None => kind = Some(key_kind),
+            Some(kind) if kind != key_kind => {
+                diag.error(
+                    &entry.key,
+                    format!(
+                        "expected {}, like the other keys of the map",
+                        kind.describe()
+                    ),
+                );
+                continue;
+            }
+            Some(_) => {}
+        }
+        if ignore_ascii_case {
+            if key_kind == KeyKind::Int {
+                diag.error(
+                    &entry.key,
+                    "`#![ignore_ascii_case]` is only supported for string and byte string keys",
+                );
+                continue;
+            }
+            bytes.make_ascii_lowercase();
+        }
+        if let Some(first) = seen.get(&bytes) {
+            let message = if ignore_ascii_case {
+                "duplicate key (keys are compared ignoring the case of ASCII letters)"
+            } else {
+                "duplicate key"
+            };
+            diag.error_with_help(
+                &entry.key,
+                message,
+                format!("the key was first used as `{}`", quote!(#first)),
+            );
+            continue;
+        }
+        seen.insert(bytes.clone(), &entry.key);
+        keys.push(bytes);
+    }
+    diag.finish(quote! {})?;
+    let hash = if keys.is_empty() {
+        PerfectHash {
+            seed: 0,
+            displacements: Vec::new(),
+            slots: Vec::new(),
+        }
+    } else {
+        (0..MAX_SEEDS)
+            .find_map(|i| self::try_seed(&keys, self::mix(i)))
+            .ok_or_else(|| {
+                syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "could not find a perfect hash function for these keys",
+                )
+            })?
+    };
+    let seed = hash.seed;
+    let displacements = hash
+        .displacements
+        .iter()
+        .map(|(d1, d2)| quote! { (#d1, #d2) });
+    let slots = hash.slots.iter().map(|i| {
+        let Entry { key, value } = &entries[*i];
+        match kind {
+            // byte string literals are arrays, and the keys are slices
+            Some(KeyKind::ByteStr) => quote! { (#key as &[u8], #value) },
+            _ => quote! { (#key, #value) },
+        }
+    });
+    Ok(quote! {
+        ::bagel::map::StaticMap {
+            __seed: #seed,
+            __ignore_ascii_case: #ignore_ascii_case,
+            __displacements: &[#(#displacements),*],
+            __entries: &[#(#slots),*],
+        }
+    })
+}
@@ -2,34 +2,346 @@
 //!
 
 use crate::util;
-use crate::util::ATTR_PHANTOM;
+use crate::util::{
+    Atomic, AtomicOrdering, Collection, Diagnostics, DocTemplate, FieldFilter, Method, MethodAttrs,
+    ATTR_BAGEL, ATTR_PHANTOM,
+};
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, DeriveInput, Ident};
+use quote::{quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, DeriveInput, Generics, Ident, Lit, Meta, NestedMeta, Type,
+};
 
+/// The attribute for constant (compile-time) setters
+const ATTR_CONST_STOR: &str = "stor_const";
 const ATTR_STOR_SKIP: &str = "stor_skip";
+const ATTR_STOR: &str = "stor";
+/// The items accepted by the `#[stor(...)]` attribute on a field (the setter kinds, `option` and
+/// `attrs`)
+const STOR_FIELD_ITEMS: &[&str] = &[
+    "set",
+    "replace",
+    "take",
+    "update",
+    "collection",
+    "option",
+    "attrs",
+];
+/// The items accepted by the `#[stor(...)]` attribute on the struct
+const STOR_LIST_ITEMS: &[&str] = &[
+    "set",
+    "replace",
+    "take",
+    "update",
+    "collection",
+    "only",
+    "except",
+    "ordering",
+    "doc",
+    "attrs",
+];
+
+/// The kinds of setters to generate for a field
+#[derive(Clone, Copy)]
+struct SetterKinds {
+    /// `set_<field>(&mut self, v)`
+    set: bool,
+    /// `replace_<field>(&mut self, v) -> T`
+    replace: bool,
+    /// `take_<field>(&mut self) -> T`
+    take: bool,
+    /// `update_<field>(&mut self, f)`
+    update: bool,
+    /// `push_<item>`, `insert_<item>`, `remove_<item>`, `extend_<field>`, `clear_<field>` and
+    /// `<field>_len` for collection fields
+    collection: bool,
+}
+
+impl SetterKinds {
+    /// Without an explicit selection, only setters are generated
+    const DEFAULT: Self = Self {
+        set: true,
+        replace: false,
+        take: false,
+        update: false,
+        collection: false,
+    };
+    const NONE: Self = Self {
+        set: false,
+        replace: false,
+        take: false,
+        update: false,
+        collection: false,
+    };
+    /// Select the given kind, returning false if it isn't a known kind
+    fn select(&mut self, kind: &str) -> bool {
+        match kind {
+            "set" => self.set = true,
+            "replace" => self.replace = true,
+            "take" => self.take = true,
+            "update" => self.update = true,
+            "collection" => self.collection = true,
+            _ => return false,
+        }
+        true
+    }
+    /// Report the selected kinds that need references to the field, which a packed struct can't
+    /// provide
+    fn reject_references(&self, attr: &Attribute, diag: &mut Diagnostics) {
+        if self.collection {
+            diag.error(
+                attr,
+                "`collection` setters can't be generated for a `#[repr(packed)]` struct, since \
+                references to its fields may be unaligned",
+            );
+        }
+    }
+}
 
 pub(crate) fn derive_stor(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = parse_macro_input!(input);
-    let struct_name = ast.ident.clone();
+    expand(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut diag = Diagnostics::default();
+    let struct_name = &ast.ident;
     err_if_subattr_on_primary_attr!(
+        diag,
         "entire struct",
         // a struct cannot be entirely phantom
         ATTR_PHANTOM in ast.attrs,
         // marking an entire struct to be skipped is useless
         ATTR_STOR_SKIP in ast.attrs,
+        ATTR_BAGEL in ast.attrs,
     );
 
     let (impl_gen, ty_gen, where_clause) = &ast.generics.split_for_impl();
-    let fields = ok_else_ret!(util::get_struct_field_names(&ast));
-    if !fields.is_empty() {
-        let mut q = quote!();
-        for (field, ty, attrs) in fields {
-            let is_skipped = ok_else_ret!(util::single_instance_of_attr(&attrs, ATTR_STOR_SKIP));
-            let is_phantom = ok_else_ret!(util::single_instance_of_attr(attrs, util::ATTR_PHANTOM));
-            if !(is_skipped && is_phantom) {
-                // not skipped or phantom, so go ahead
-                let field_name_str = field.to_string();
+    let fields = util::get_struct_field_names(ast)?;
+    let mut struct_kinds = None;
+    let mut filter = FieldFilter::default();
+    let mut ordering = None;
+    let mut template = None;
+    // setters are `#[inline]`, unless other attributes are given with `attrs(...)`
+    let mut method_attrs = MethodAttrs::Defaults { must_use: false };
+    for item in util::get_attr_list(&ast.attrs, ATTR_STOR, &mut diag) {
+        if filter.parse(&item, ATTR_STOR, &fields, &mut diag) {
+            continue;
+        }
+        if let Some(attrs) = MethodAttrs::parse(&item, &mut diag) {
+            method_attrs = attrs;
+            continue;
+        }
+        if let NestedMeta::Meta(Meta::NameValue(nv)) = &item {
+            if nv.path.is_ident("ordering") {
+                // the default memory ordering for atomic fields
+                ordering = AtomicOrdering::parse(&nv.lit, &mut diag);
+                if ordering == Some(AtomicOrdering::Acquire) {
+                    // a store can't acquire, and weakening it to `Relaxed` would be a surprise
+                    diag.error_with_help(
+                        &nv.lit,
+                        "`Acquire` is not a valid ordering for `store_<field>`",
+                        "use `AcqRel` (which stores with `Release`) or `SeqCst`",
+                    );
+                }
+                continue;
+            }
+            if nv.path.is_ident("doc") {
+                // a template for the doc comments of every generated method
+                template = DocTemplate::parse(&nv.lit, &mut diag);
+                continue;
+            }
+        }
+        let kind = util::nested_name(&item);
+        let kinds = struct_kinds.get_or_insert(SetterKinds::NONE);
+        if !kind.map(|kind| kinds.select(&kind)).unwrap_or(false) {
+            util::unknown_nested(&item, ATTR_STOR, STOR_LIST_ITEMS, &mut diag);
+        }
+    }
+    let struct_kinds = struct_kinds.unwrap_or(SetterKinds::DEFAULT);
+    // fields of packed structs may be unaligned, so they can't be accessed by reference
+    let is_packed = util::is_packed(&ast.attrs);
+    if is_packed {
+        if let Some(attr) = ast.attrs.iter().find(|attr| attr.path.is_ident(ATTR_STOR)) {
+            struct_kinds.reject_references(attr, &mut diag);
+        }
+    }
+    let is_const = util::single_instance_of_attr(&ast.attrs, ATTR_CONST_STOR, &mut diag);
+    // get function header
+    let func = util::get_func_header(&ast.attrs, ATTR_CONST_STOR, &mut diag);
+    // `Gtor` already generates `set_<field>(&self, v)` for these `Cell` fields
+    let cell_setters = crate::gtor::inner_cell_setters(ast);
+    let mut q = quote!();
+    for (field, ty, attrs) in fields {
+        err_if_subattr_on_primary_attr!(
+            diag,
+            "field",
+            // marking stor_const on a field is invalid
+            ATTR_CONST_STOR in attrs,
+        );
+        let is_skipped = util::single_instance_of_attr(attrs, ATTR_STOR_SKIP, &mut diag)
+            | util::is_bagel_skipped(attrs, &mut diag);
+        let is_phantom = util::single_instance_of_attr(attrs, ATTR_PHANTOM, &mut diag);
+        // a selection on the field overrides the selection on the struct
+        let mut field_kinds = None;
+        let mut field_method_attrs = None;
+        let mut is_explicitly_option = false;
+        // the singular name for the collection helpers, if given with `collection = "..."`
+        let mut collection_singular = None;
+        for item in util::get_attr_list(attrs, ATTR_STOR, &mut diag) {
+            let kind = util::nested_name(&item);
+            if kind.as_deref() == Some("option") {
+                is_explicitly_option = true;
+                continue;
+            }
+            if let Some(attrs) = MethodAttrs::parse(&item, &mut diag) {
+                field_method_attrs = Some(attrs);
+                continue;
+            }
+            let kinds = field_kinds.get_or_insert(SetterKinds::NONE);
+            if let NestedMeta::Meta(Meta::NameValue(nv)) = &item {
+                if nv.path.is_ident("collection") {
+                    kinds.collection = true;
+                    match &nv.lit {
+                        // the name is only used in the names of the methods, so keywords are fine
+                        Lit::Str(singular) => match singular.parse_with(Ident::parse_any) {
+                            Ok(singular) => collection_singular = Some(singular.unraw()),
+                            Err(_) => diag.error(singular, "expected a valid identifier"),
+                        },
+                        lit => diag.error(lit, "expected a string literal"),
+                    }
+                    continue;
+                }
+            }
+            if !kind.map(|kind| kinds.select(&kind)).unwrap_or(false) {
+                util::unknown_nested(&item, ATTR_STOR, STOR_FIELD_ITEMS, &mut diag);
+            }
+        }
+        if is_skipped || is_phantom {
+            for attr in attrs.iter().filter(|attr| attr.path.is_ident(ATTR_STOR)) {
+                diag.error(attr, "selecting setters on a skipped field is invalid");
+            }
+        }
+        if let (true, Some(kinds), Some(attr)) = (
+            is_packed && !(is_skipped || is_phantom),
+            field_kinds,
+            attrs.iter().find(|attr| attr.path.is_ident(ATTR_STOR)),
+        ) {
+            kinds.reject_references(attr, &mut diag);
+        }
+        let mut kinds = field_kinds.unwrap_or(struct_kinds);
+        if cell_setters.contains(&field) {
+            kinds.set = false;
+        }
+        // the `T` for `Option<T>` fields (and for fields explicitly marked as options, which may be
+        // hidden behind an alias)
+        let option_inner = match util::option_inner(ty) {
+            Some(inner) => Some(quote! { #inner }),
+            None if is_explicitly_option => {
+                Some(quote! { <#ty as ::bagel::__private::OptionInner>::Inner })
+            }
+            None => None,
+        };
+        if !(is_skipped || is_phantom) && filter.includes(field) {
+            // not skipped, not phantom and not filtered out, so go ahead. the methods for this field are
+            // generated separately, so that the field's attributes can be carried over to them
+            let field_name_str = field.to_string();
+            if is_packed {
+                let setters =
+                    self::packed_setters(field, ty, kinds, &option_inner, &func, is_const, ast);
+                let setters = util::finish_methods(
+                    setters,
+                    field,
+                    attrs,
+                    template.as_ref(),
+                    field_method_attrs.as_ref().unwrap_or(&method_attrs),
+                    struct_name,
+                );
+                q = quote! {
+                    #q
+                    #setters
+                };
+                continue;
+            }
+            let mut methods = Vec::new();
+            match (kinds.collection, util::collection(ty)) {
+                (true, Some(collection)) => {
+                    let singular = collection_singular.unwrap_or_else(|| {
+                        Ident::new(&self::singular(&field_name_str), field.span())
+                    });
+                    methods.extend(self::collection_helpers(
+                        field,
+                        &singular,
+                        collection,
+                        struct_name,
+                    ));
+                }
+                // selecting collection helpers for the whole struct only affects collection fields
+                (true, None) if field_kinds.is_some() => diag.error(
+                    ty,
+                    "collection helpers can only be generated for the `Vec`, `VecDeque`, `HashMap`, \
+                    `BTreeMap`, `HashSet`, `BTreeSet` and `String` fields of the standard library",
+                ),
+                _ => {}
+            }
+            if let (true, Some(atomic)) = (kinds.set, util::atomic(ty)) {
+                // atomics are written through `&self` instead of being replaced
+                methods.extend(self::atomic_setters(field, atomic, ordering, struct_name));
+            } else if let (true, Some(inner)) = (kinds.set, &option_inner) {
+                let fname = format!("set_{field}", field = field_name_str);
+                let doc_comment = format!(
+                    "Sets the value for the `{field}` field in struct [`{struct_name}`] to `Some(value)`",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+                methods.push(Method::unit(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        #func #fname(&mut self, #field: #inner) {
+                            self.#field = ::core::option::Option::Some(#field);
+                        }
+                    },
+                ));
+                let fname = format!("clear_{field}", field = field_name_str);
+                let doc_comment = format!(
+                    "Sets the value for the `{field}` field in struct [`{struct_name}`] to `None`",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+                methods.push(Method::unit(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        #func #fname(&mut self) {
+                            self.#field = ::core::option::Option::None;
+                        }
+                    },
+                ));
+                let fname = format!("get_or_insert_{field}_with", field = field_name_str);
+                let doc_comment = format!(
+                    "Returns a mutable reference to the value in the `{field}` field in struct [`{struct_name}`], \
+                    first inserting the value returned by the closure if it is `None`",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+                methods.push(Method::returning(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        pub fn #fname(&mut self, f: impl ::core::ops::FnOnce() -> #inner) -> &mut #inner {
+                            self.#field.get_or_insert_with(f)
+                        }
+                    },
+                ));
+            } else if kinds.set {
                 let mut fname = "set_".to_owned();
                 fname.push_str(&field_name_str);
                 let doc_comment = format!(
@@ -38,22 +350,588 @@ pub(crate) fn derive_stor(input: TokenStream) -> TokenStream {
                     field = field_name_str
                 );
                 let fname = Ident::new(&fname, field.span());
-                q = quote! {
-                    #q
-                    #[doc = #doc_comment]
-                    pub fn #fname(&mut self, #field: #ty) {
-                        self.#field = #field;
-                    }
-                };
+                methods.push(Method::unit(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        #func #fname(&mut self, #field: #ty) {
+                            self.#field = #field;
+                        }
+                    },
+                ));
             }
-        }
-        q = quote! {
-            impl #impl_gen #struct_name #ty_gen #where_clause {
-                #q
+            if kinds.replace {
+                let fname = format!("replace_{field}", field = field_name_str);
+                let doc_comment = format!(
+                    "Sets the value for the `{field}` field in struct [`{struct_name}`], returning the previous value",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+                methods.push(Method::returning(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        #func #fname(&mut self, #field: #ty) -> #ty {
+                            ::core::mem::replace(&mut self.#field, #field)
+                        }
+                    },
+                ));
             }
+            if let (true, Some(_)) = (kinds.take || kinds.set, &option_inner) {
+                let fname = format!("take_{field}", field = field_name_str);
+                let doc_comment = format!(
+                    "Takes the value of the `{field}` field in struct [`{struct_name}`], leaving `None` in its place",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+                methods.push(Method::returning(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        #func #fname(&mut self) -> #ty {
+                            self.#field.take()
+                        }
+                    },
+                ));
+            } else if kinds.take {
+                let fname = format!("take_{field}", field = field_name_str);
+                let doc_comment = format!(
+                    "Takes the value of the `{field}` field in struct [`{struct_name}`], leaving the default value in its place",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+                let (bound, default) = self::take_default(ty, &ast.generics, is_const);
+                methods.push(Method::returning(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        #func #fname(&mut self) -> #ty where #bound {
+                            ::core::mem::replace(&mut self.#field, #default)
+                        }
+                    },
+                ));
+            }
+            if kinds.update {
+                let fname = format!("update_{field}", field = field_name_str);
+                let doc_comment = format!(
+                    "Updates the value of the `{field}` field in struct [`{struct_name}`] using the given closure",
+                    struct_name = struct_name,
+                    field = field_name_str
+                );
+                let fname = Ident::new(&fname, field.span());
+                methods.push(Method::returning(
+                    &fname,
+                    doc_comment,
+                    quote! {
+                        pub fn #fname<R>(&mut self, f: impl ::core::ops::FnOnce(&mut #ty) -> R) -> R {
+                            f(&mut self.#field)
+                        }
+                    },
+                ));
+            }
+            let methods = util::finish_methods(
+                methods,
+                field,
+                attrs,
+                template.as_ref(),
+                field_method_attrs.as_ref().unwrap_or(&method_attrs),
+                struct_name,
+            );
+            q = quote! {
+                #q
+                #methods
+            };
+        }
+    }
+    diag.finish(quote! {
+        impl #impl_gen #struct_name #ty_gen #where_clause {
+            #q
+        }
+    })
+}
+
+/// Returns the bound and the default value for the `take_<field>` setter. Constant setters can't
+/// use `Default`, so they use `Constdef` instead. A bound on a concrete type is checked with the
+/// struct, so a field that can't be taken is reported at the field instead
+fn take_default(
+    ty: &Type,
+    generics: &Generics,
+    is_const: bool,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    match (util::mentions_type_param(ty, generics), is_const) {
+        (true, true) => (
+            quote! { #ty: ::bagel::Constdef },
+            quote! { <#ty as ::bagel::Constdef>::DEFAULT },
+        ),
+        (true, false) => (
+            quote! { #ty: ::core::default::Default },
+            quote! { <#ty as ::core::default::Default>::default() },
+        ),
+        (false, true) => (
+            quote_spanned! {ty.span()=> #ty: ::bagel::__private::ConstTakeField },
+            quote! { <#ty as ::bagel::__private::ConstTakeField>::TAKE_DEFAULT },
+        ),
+        (false, false) => (
+            quote_spanned! {ty.span()=> #ty: ::bagel::__private::TakeField },
+            quote! { <#ty as ::bagel::__private::TakeField>::take_default() },
+        ),
+    }
+}
+
+/// Generates the setters for a field of a packed struct. Since references to the field may be
+/// unaligned, the value is copied in and out instead (which needs the field to be `Copy`)
+fn packed_setters(
+    field: &Ident,
+    ty: &Type,
+    kinds: SetterKinds,
+    option_inner: &Option<proc_macro2::TokenStream>,
+    func: &proc_macro2::TokenStream,
+    is_const: bool,
+    ast: &DeriveInput,
+) -> Vec<Method> {
+    let struct_name = &ast.ident;
+    let method = |prefix: &str| Ident::new(&format!("{}{}", prefix, field), field.span());
+    let doc = |what: &str| {
+        format!(
+            "{} the `{}` field in struct [`{}`]",
+            what, field, struct_name
+        )
+    };
+    let (bound, check) = util::packed_check(ty, &ast.generics);
+    let read = quote! { self.#field };
+    let write = |value: proc_macro2::TokenStream| quote! { self.#field = #value };
+    let mut methods = Vec::new();
+    if kinds.set {
+        let set_fn = method("set_");
+        if let Some(inner) = option_inner {
+            let (set_doc, clear_doc) = (
+                doc("Sets (to `Some(value)`) the value for"),
+                doc("Sets (to `None`) the value for"),
+            );
+            let clear_fn = method("clear_");
+            let (set, clear) = (
+                write(quote! { ::core::option::Option::Some(#field) }),
+                write(quote! { ::core::option::Option::None }),
+            );
+            methods.push(Method::unit(
+                &set_fn,
+                set_doc,
+                quote! {
+                    #func #set_fn(&mut self, #field: #inner) where #bound {
+                        #check
+                        #set
+                    }
+                },
+            ));
+            methods.push(Method::unit(
+                &clear_fn,
+                clear_doc,
+                quote! {
+                    #func #clear_fn(&mut self) where #bound {
+                        #check
+                        #clear
+                    }
+                },
+            ));
+        } else {
+            let set_doc = doc("Sets the value for");
+            let set = write(quote! { #field });
+            methods.push(Method::unit(
+                &set_fn,
+                set_doc,
+                quote! {
+                    #func #set_fn(&mut self, #field: #ty) where #bound {
+                        #check
+                        #set
+                    }
+                },
+            ));
+        }
+    }
+    if kinds.replace {
+        let replace_fn = method("replace_");
+        let replace_doc = doc("Sets (returning the previous value) the value for");
+        let replace = write(quote! { #field });
+        methods.push(Method::returning(
+            &replace_fn,
+            replace_doc,
+            quote! {
+                #func #replace_fn(&mut self, #field: #ty) -> #ty where #bound {
+                    #check
+                    let previous = #read;
+                    #replace;
+                    previous
+                }
+            },
+        ));
+    }
+    if kinds.take || (kinds.set && option_inner.is_some()) {
+        let take_fn = method("take_");
+        // `Option` fields are left as `None`, and every other field uses its default value
+        let (take_doc, default, default_bound) = if option_inner.is_some() {
+            (
+                doc("Takes (leaving `None` in its place) the value of"),
+                quote! { ::core::option::Option::None },
+                quote! {},
+            )
+        } else {
+            let (default_bound, default) = self::take_default(ty, &ast.generics, is_const);
+            (
+                doc("Takes (leaving the default value in its place) the value of"),
+                default,
+                quote! { #default_bound, },
+            )
         };
-        q.into()
+        let take = write(default);
+        methods.push(Method::returning(
+            &take_fn,
+            take_doc,
+            quote! {
+                #func #take_fn(&mut self) -> #ty where #default_bound #bound {
+                    #check
+                    let value = #read;
+                    #take;
+                    value
+                }
+            },
+        ));
+    }
+    if kinds.update {
+        let update_fn = method("update_");
+        let update_doc = doc("Updates (using the given closure) the value of");
+        let update = write(quote! { value });
+        methods.push(Method::returning(
+            &update_fn,
+            update_doc,
+            quote! {
+                pub fn #update_fn<R>(&mut self, f: impl ::core::ops::FnOnce(&mut #ty) -> R) -> R
+                where
+                    #bound
+                {
+                    #check
+                    let mut value = #read;
+                    let ret = f(&mut value);
+                    #update;
+                    ret
+                }
+            },
+        ));
+    }
+    methods
+}
+
+/// Generates `store_<field>`, `swap_<field>`, `compare_exchange_<field>` and (for integers)
+/// `fetch_add_<field>` for an atomic field. If a default `ordering` is set, the methods use it instead
+/// of accepting orderings as arguments
+fn atomic_setters(
+    field: &Ident,
+    atomic: Atomic,
+    ordering: Option<AtomicOrdering>,
+    struct_name: &Ident,
+) -> Vec<Method> {
+    let method = |prefix: &str| Ident::new(&format!("{}{}", prefix, field), field.span());
+    let doc = |what: &str| {
+        format!(
+            "{} the atomic `{}` field in struct [`{}`]",
+            what, field, struct_name
+        )
+    };
+    let (store_fn, swap_fn, compare_exchange_fn, fetch_add_fn) = (
+        method("store_"),
+        method("swap_"),
+        method("compare_exchange_"),
+        method("fetch_add_"),
+    );
+    let (store_doc, swap_doc, compare_exchange_doc, fetch_add_doc) = (
+        doc("Stores a value into"),
+        doc("Stores a value into (returning the previous value)"),
+        doc("Stores a value into (if the current value is the same as `current`)"),
+        doc("Adds to the current value (returning the previous value) of"),
+    );
+    let value = atomic.value;
+    // the ordering arguments (if no default was set) along with the orderings to use
+    let ordering_ty = quote! { ::core::sync::atomic::Ordering };
+    let (store_args, store_ordering, rmw_args, rmw_ordering, cx_args, cx_orderings) = match ordering
+    {
+        Some(ordering) => {
+            let (store, failure) = (ordering.for_store(), ordering.for_load());
+            (
+                quote! {},
+                quote! { #store },
+                quote! {},
+                quote! { #ordering },
+                quote! {},
+                quote! { #ordering, #failure },
+            )
+        }
+        None => (
+            quote! { , order: #ordering_ty },
+            quote! { order },
+            quote! { , order: #ordering_ty },
+            quote! { order },
+            quote! { , success: #ordering_ty, failure: #ordering_ty },
+            quote! { success, failure },
+        ),
+    };
+    let mut methods = vec![
+        Method::unit(
+            &store_fn,
+            store_doc,
+            quote! {
+                pub fn #store_fn(&self, value: #value #store_args) {
+                    self.#field.store(value, #store_ordering)
+                }
+            },
+        ),
+        Method::returning(
+            &swap_fn,
+            swap_doc,
+            quote! {
+                pub fn #swap_fn(&self, value: #value #rmw_args) -> #value {
+                    self.#field.swap(value, #rmw_ordering)
+                }
+            },
+        ),
+        Method::returning(
+            &compare_exchange_fn,
+            compare_exchange_doc,
+            quote! {
+                pub fn #compare_exchange_fn(
+                    &self,
+                    current: #value,
+                    new: #value
+                    #cx_args
+                ) -> ::core::result::Result<#value, #value> {
+                    self.#field.compare_exchange(current, new, #cx_orderings)
+                }
+            },
+        ),
+    ];
+    if atomic.is_integer {
+        methods.push(Method::returning(
+            &fetch_add_fn,
+            fetch_add_doc,
+            quote! {
+                pub fn #fetch_add_fn(&self, value: #value #rmw_args) -> #value {
+                    self.#field.fetch_add(value, #rmw_ordering)
+                }
+            },
+        ));
+    }
+    methods
+}
+
+/// Returns the singular form of a (plural) field name, with the rule documented on `Stor`: a trailing
+/// `ies` becomes `y`, and otherwise a trailing `s` (but not `ss`) is removed. Names that don't
+/// follow the rule (like `status`) need `collection = "..."`
+fn singular(field: &str) -> String {
+    if let Some(stem) = field.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if field.ends_with('s') && !field.ends_with("ss") && field.len() > 1 {
+        field[..field.len() - 1].to_owned()
     } else {
-        return "".parse().unwrap();
+        field.to_owned()
     }
 }
+
+/// Generates the helpers for a collection field, using `singular` to name the methods that operate
+/// on a single element
+fn collection_helpers(
+    field: &Ident,
+    singular: &Ident,
+    collection: Collection<'_>,
+    struct_name: &Ident,
+) -> Vec<Method> {
+    let method = |prefix: &str, name: &Ident, suffix: &str| {
+        Ident::new(&format!("{}{}{}", prefix, name, suffix), field.span())
+    };
+    let doc = |what: &str| {
+        format!(
+            "{} the `{}` field in struct [`{}`]",
+            what, field, struct_name
+        )
+    };
+    let (push_fn, insert_fn, remove_fn) = (
+        method("push_", singular, ""),
+        method("insert_", singular, ""),
+        method("remove_", singular, ""),
+    );
+    let (extend_fn, clear_fn, len_fn) = (
+        method("extend_", field, ""),
+        method("clear_", field, ""),
+        method("", field, "_len"),
+    );
+    let (extend_doc, clear_doc, len_doc) = (
+        doc("Extends the collection in"),
+        doc("Removes all elements from the collection in"),
+        doc("Returns the number of elements in the collection in"),
+    );
+    let mut helpers = match collection {
+        Collection::Sequence { elem, is_deque } => {
+            let push = if is_deque {
+                quote! { push_back }
+            } else {
+                quote! { push }
+            };
+            let push_doc = doc("Appends an element to the back of the collection in");
+            vec![
+                Method::unit(
+                    &push_fn,
+                    push_doc,
+                    quote! {
+                        pub fn #push_fn(&mut self, value: #elem) {
+                            self.#field.#push(value)
+                        }
+                    },
+                ),
+                Method::unit(
+                    &extend_fn,
+                    extend_doc,
+                    quote! {
+                        pub fn #extend_fn(&mut self, iter: impl ::core::iter::IntoIterator<Item = #elem>) {
+                            ::core::iter::Extend::extend(&mut self.#field, iter)
+                        }
+                    },
+                ),
+            ]
+        }
+        Collection::Map {
+            key,
+            value,
+            is_ordered,
+        } => {
+            let bound = if is_ordered {
+                quote! { ::core::cmp::Ord }
+            } else {
+                quote! { ::core::hash::Hash + ::core::cmp::Eq }
+            };
+            let (insert_doc, remove_doc) = (
+                doc("Inserts a key-value pair (returning the previous value, if any) into the map in"),
+                doc("Removes a key (returning its value, if any) from the map in"),
+            );
+            vec![
+                Method::returning(
+                    &insert_fn,
+                    insert_doc,
+                    quote! {
+                        pub fn #insert_fn(&mut self, key: #key, value: #value) -> ::core::option::Option<#value> {
+                            self.#field.insert(key, value)
+                        }
+                    },
+                ),
+                Method::returning(
+                    &remove_fn,
+                    remove_doc,
+                    quote! {
+                        pub fn #remove_fn<Q>(&mut self, key: &Q) -> ::core::option::Option<#value>
+                        where
+                            #key: ::core::borrow::Borrow<Q>,
+                            Q: #bound + ?Sized,
+                        {
+                            self.#field.remove(key)
+                        }
+                    },
+                ),
+                Method::unit(
+                    &extend_fn,
+                    extend_doc,
+                    quote! {
+                        pub fn #extend_fn(&mut self, iter: impl ::core::iter::IntoIterator<Item = (#key, #value)>) {
+                            ::core::iter::Extend::extend(&mut self.#field, iter)
+                        }
+                    },
+                ),
+            ]
+        }
+        Collection::Set { elem, is_ordered } => {
+            let bound = if is_ordered {
+                quote! { ::core::cmp::Ord }
+            } else {
+                quote! { ::core::hash::Hash + ::core::cmp::Eq }
+            };
+            let (insert_doc, remove_doc) = (
+                doc("Inserts a value (returning true if it wasn't present) into the set in"),
+                doc("Removes a value (returning true if it was present) from the set in"),
+            );
+            vec![
+                Method::returning(
+                    &insert_fn,
+                    insert_doc,
+                    quote! {
+                        pub fn #insert_fn(&mut self, value: #elem) -> bool {
+                            self.#field.insert(value)
+                        }
+                    },
+                ),
+                Method::returning(
+                    &remove_fn,
+                    remove_doc,
+                    quote! {
+                        pub fn #remove_fn<Q>(&mut self, value: &Q) -> bool
+                        where
+                            #elem: ::core::borrow::Borrow<Q>,
+                            Q: #bound + ?Sized,
+                        {
+                            self.#field.remove(value)
+                        }
+                    },
+                ),
+                Method::unit(
+                    &extend_fn,
+                    extend_doc,
+                    quote! {
+                        pub fn #extend_fn(&mut self, iter: impl ::core::iter::IntoIterator<Item = #elem>) {
+                            ::core::iter::Extend::extend(&mut self.#field, iter)
+                        }
+                    },
+                ),
+            ]
+        }
+        Collection::String => {
+            let push_doc = doc("Appends a string slice to the string in");
+            vec![
+                Method::unit(
+                    &push_fn,
+                    push_doc,
+                    quote! {
+                        pub fn #push_fn(&mut self, value: &str) {
+                            self.#field.push_str(value)
+                        }
+                    },
+                ),
+                Method::unit(
+                    &extend_fn,
+                    extend_doc,
+                    quote! {
+                        pub fn #extend_fn(&mut self, iter: impl ::core::iter::IntoIterator<Item = char>) {
+                            ::core::iter::Extend::extend(&mut self.#field, iter)
+                        }
+                    },
+                ),
+            ]
+        }
+    };
+    // every collection has these
+    helpers.push(Method::unit(
+        &clear_fn,
+        clear_doc,
+        quote! {
+            pub fn #clear_fn(&mut self) {
+                self.#field.clear()
+            }
+        },
+    ));
+    helpers.push(Method::returning(
+        &len_fn,
+        len_doc,
+        quote! {
+            pub fn #len_fn(&self) -> usize {
+                self.#field.len()
+            }
+        },
+    ));
+    helpers
+}
@@ -1,65 +1,798 @@
-use proc_macro::TokenStream;
-use quote::quote;
-use syn::{spanned::Spanned, Attribute, Data, DataStruct, DeriveInput, Fields, Ident, Type};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{quote, quote_spanned, ToTokens};
+use std::collections::HashSet;
+use std::fmt::Display;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Data, DataStruct, DeriveInput, Fields, GenericArgument, Generics, Ident, Lit, Meta,
+    MetaList, NestedMeta, PathArguments, Type, TypePath,
+};
 
 pub(crate) const ATTR_PHANTOM: &str = "phantom";
+/// The attribute shared by all derives (for example, `#[bagel(skip)]`)
+pub(crate) const ATTR_BAGEL: &str = "bagel";
+/// The items accepted by the `#[bagel(...)]` attribute
+const BAGEL_LIST_ITEMS: &[&str] = &["skip"];
 
 gen_typeset! {
     u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, str, bool, usize, isize, char, f32, f64
 }
 
+pub(crate) type NamedFieldInfo<'a> = (&'a Ident, &'a Type, &'a Vec<Attribute>);
+
+/// Collects every error found while expanding a derive, so that all of them can be reported
+/// in a single compile
+#[derive(Default)]
+pub(crate) struct Diagnostics {
+    errors: Option<syn::Error>,
+}
+
+impl Diagnostics {
+    pub(crate) fn push(&mut self, error: syn::Error) {
+        match &mut self.errors {
+            Some(errors) => errors.combine(error),
+            None => self.errors = Some(error),
+        }
+    }
+    /// Add an error pointing at the given tokens
+    pub(crate) fn error(&mut self, tokens: impl ToTokens, message: impl Display) {
+        self.push(syn::Error::new_spanned(tokens, message))
+    }
+    /// Add an error pointing at the given tokens, along with a `help:` hint
+    pub(crate) fn error_with_help(
+        &mut self,
+        tokens: impl ToTokens,
+        message: impl Display,
+        help: impl Display,
+    ) {
+        self.error(tokens, format!("{}\n= help: {}", message, help))
+    }
+    /// Record the error (if any), returning the value otherwise
+    pub(crate) fn ok<T>(&mut self, result: syn::Result<T>) -> Option<T> {
+        match result {
+            Ok(v) => Some(v),
+            Err(e) => {
+                self.push(e);
+                None
+            }
+        }
+    }
+    /// Returns the generated tokens if no errors were found; otherwise all the errors are
+    /// returned
+    pub(crate) fn finish(self, tokens: TokenStream) -> syn::Result<TokenStream> {
+        match self.errors {
+            Some(e) => Err(e),
+            None => Ok(tokens),
+        }
+    }
+}
+
 /// Returns the field names and their corresponding type from the AST (returning an error
 /// if it isn't a struct)
-pub fn get_struct_field_names(
-    ast: &DeriveInput,
-) -> Result<Vec<(&Ident, &Type, &Vec<Attribute>)>, TokenStream> {
-    let fields = match &ast.data {
+pub(crate) fn get_struct_field_names(ast: &DeriveInput) -> syn::Result<Vec<NamedFieldInfo<'_>>> {
+    match &ast.data {
         Data::Struct(DataStruct {
             fields: Fields::Named(fields),
             ..
-        }) => &fields.named,
-        _ => {
-            return Err(
-                syn::Error::new_spanned(ast, "this macro can only be used on structs")
-                    .into_compile_error()
-                    .into(),
-            );
-        }
-    };
-    if fields.is_empty() {
-        Ok(Vec::new())
-    } else {
-        Ok(fields
+        }) => Ok(fields
+            .named
             .iter()
             .map(|field| {
                 let fname = field.ident.as_ref().unwrap();
                 (fname, &field.ty, &field.attrs)
             })
-            .collect())
+            .collect()),
+        _ => Err(syn::Error::new_spanned(
+            ast,
+            "this macro can only be used on structs with named fields",
+        )),
+    }
+}
+
+/// Returns the first instance of the given attribute, reporting every duplicate instance
+pub(crate) fn get_attr<'a>(
+    attrs: &'a [Attribute],
+    target: &str,
+    diag: &mut Diagnostics,
+) -> Option<&'a Attribute> {
+    let mut instances = attrs.iter().filter(|attr| attr.path.is_ident(target));
+    let first = instances.next();
+    for duplicate in instances {
+        diag.error(
+            duplicate,
+            format!("found duplicate attributes for `{}`", target),
+        );
     }
+    first
 }
 
 pub(crate) fn single_instance_of_attr(
     attrs: &[Attribute],
     target: &str,
-) -> Result<bool, TokenStream> {
-    let mut span = None;
-    let has_attr = attrs
+    diag: &mut Diagnostics,
+) -> bool {
+    self::get_attr(attrs, target, diag).is_some()
+}
+
+/// Returns the nested items from every `#[target(...)]` attribute
+pub(crate) fn get_attr_list(
+    attrs: &[Attribute],
+    target: &str,
+    diag: &mut Diagnostics,
+) -> Vec<NestedMeta> {
+    let mut ret = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident(target)) {
+        match attr.parse_meta() {
+            Ok(Meta::List(MetaList { nested, .. })) => ret.extend(nested),
+            Ok(meta) => diag.error(
+                meta,
+                format!("expected a list of the form `#[{}(...)]`", target),
+            ),
+            Err(e) => diag.push(e),
+        }
+    }
+    ret
+}
+
+/// Returns the name of a nested attribute item, if it is a plain identifier
+pub(crate) fn nested_name(nested: &NestedMeta) -> Option<String> {
+    match nested {
+        NestedMeta::Meta(meta) => meta.path().get_ident().map(ToString::to_string),
+        NestedMeta::Lit(_) => None,
+    }
+}
+
+/// Report a nested attribute item that is not one of the `known` items
+pub(crate) fn unknown_nested(
+    nested: &NestedMeta,
+    target: &str,
+    known: &[&str],
+    diag: &mut Diagnostics,
+) {
+    let name = self::nested_name(nested);
+    let message = match &name {
+        Some(name) => format!("unknown item `{}` in `#[{}(...)]`", name, target),
+        None => format!("unknown item in `#[{}(...)]`", target),
+    };
+    match name.and_then(|name| self::suggest(&name, known)) {
+        Some(suggestion) => {
+            diag.error_with_help(nested, message, format!("did you mean `{}`?", suggestion))
+        }
+        None => diag.error(nested, message),
+    }
+}
+
+/// Returns the closest match for `name` among the `candidates`, if any of them is close enough
+/// to be a likely typo
+pub(crate) fn suggest<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (self::edit_distance(name, candidate), *candidate))
+        .filter(|(distance, candidate)| *distance <= (candidate.len() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Returns the Levenshtein distance between the two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            current[j + 1] = (prev[j] + (ca != *cb) as usize)
+                .min(prev[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+/// Returns true if the field was marked with `#[bagel(skip)]`, which skips it in every derive
+pub(crate) fn is_bagel_skipped(attrs: &[Attribute], diag: &mut Diagnostics) -> bool {
+    let mut is_skipped = false;
+    for item in self::get_attr_list(attrs, ATTR_BAGEL, diag) {
+        match self::nested_name(&item) {
+            Some(name) if name == "skip" => is_skipped = true,
+            _ => self::unknown_nested(&item, ATTR_BAGEL, BAGEL_LIST_ITEMS, diag),
+        }
+    }
+    is_skipped
+}
+
+/// The fields selected by the `only(...)` and `except(...)` items of a container attribute
+/// such as `#[gtor(only(a, b))]`
+#[derive(Default)]
+pub(crate) struct FieldFilter {
+    only: Option<HashSet<String>>,
+    except: HashSet<String>,
+}
+
+impl FieldFilter {
+    /// Parses the item if it is an `only(...)` or `except(...)` list, returning false if it
+    /// is neither
+    pub(crate) fn parse(
+        &mut self,
+        item: &NestedMeta,
+        target: &str,
+        fields: &[NamedFieldInfo<'_>],
+        diag: &mut Diagnostics,
+    ) -> bool {
+        let list = match item {
+            NestedMeta::Meta(Meta::List(list))
+                if list.path.is_ident("only") || list.path.is_ident("except") =>
+            {
+                list
+            }
+            _ => return false,
+        };
+        let field_names: Vec<String> = fields.iter().map(|(f, _, _)| f.to_string()).collect();
+        let field_names: Vec<&str> = field_names.iter().map(String::as_str).collect();
+        let mut names = HashSet::new();
+        for nested in &list.nested {
+            let name = match nested {
+                NestedMeta::Meta(Meta::Path(path)) => path.get_ident().map(ToString::to_string),
+                _ => None,
+            };
+            match name {
+                Some(name) if field_names.contains(&name.as_str()) => {
+                    names.insert(name);
+                }
+                Some(name) => match self::suggest(&name, &field_names) {
+                    Some(suggestion) => diag.error_with_help(
+                        nested,
+                        format!("no field named `{}`", name),
+                        format!("did you mean `{}`?", suggestion),
+                    ),
+                    None => diag.error(nested, format!("no field named `{}`", name)),
+                },
+                None => diag.error(
+                    nested,
+                    format!("expected a field name in `#[{}(...)]`", target),
+                ),
+            }
+        }
+        if list.path.is_ident("only") {
+            self.only.get_or_insert_with(HashSet::new).extend(names);
+        } else {
+            self.except.extend(names);
+        }
+        true
+    }
+    /// Returns true if the field is selected by this filter
+    pub(crate) fn includes(&self, field: &Ident) -> bool {
+        let field = field.to_string();
+        self.only
+            .as_ref()
+            .map(|only| only.contains(&field))
+            .unwrap_or(true)
+            && !self.except.contains(&field)
+    }
+}
+
+/// Returns the last segment of the type's path along with its generic type arguments, if the type is
+/// a plain path (like `Vec<T>` or `std::collections::HashMap<K, V>`)
+pub(crate) fn path_type_args(ty: &Type) -> Option<(&Ident, Vec<&Type>)> {
+    let path = match ty {
+        Type::Path(TypePath { qself: None, path }) => path,
+        _ => return None,
+    };
+    let last = path.segments.last()?;
+    let args = match &last.arguments {
+        PathArguments::None => Vec::new(),
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        PathArguments::Parenthesized(_) => return None,
+    };
+    Some((&last.ident, args))
+}
+
+/// Returns true if the type is named by a bare path (like `Option<T>`) or by a path into the given
+/// module of `core`, `alloc` or `std` (like `core::option::Option<T>`, or
+/// `std::sync::atomic::AtomicU8` for the `sync::atomic` module). Types with any other path (like
+/// `parking_lot::Mutex<T>`) are not the standard library types, even if their names are the same
+fn is_std_path(ty: &Type, module: &str) -> bool {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => {
+            let segments: Vec<String> = path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect();
+            match segments.as_slice() {
+                [_] => path.leading_colon.is_none(),
+                [krate, modules @ .., _] => {
+                    (krate == "core" || krate == "alloc" || krate == "std")
+                        && modules.join("::") == module
+                }
+                [] => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Returns `T` if the type is (syntactically) an `Option<T>`
+pub(crate) fn option_inner(ty: &Type) -> Option<&Type> {
+    let is_option_path = self::is_std_path(ty, "option");
+    match self::path_type_args(ty) {
+        Some((ident, args)) if is_option_path && ident == "Option" && args.len() == 1 => {
+            Some(args[0])
+        }
+        _ => None,
+    }
+}
+
+/// The standard library collections that `Stor` can generate helpers for
+pub(crate) enum Collection<'a> {
+    /// `Vec<T>` or `VecDeque<T>`
+    Sequence { elem: &'a Type, is_deque: bool },
+    /// `HashMap<K, V>` or `BTreeMap<K, V>`
+    Map {
+        key: &'a Type,
+        value: &'a Type,
+        is_ordered: bool,
+    },
+    /// `HashSet<T>` or `BTreeSet<T>`
+    Set { elem: &'a Type, is_ordered: bool },
+    /// `String`
+    String,
+}
+
+/// Returns the kind of collection, if the type is one of the supported collections from the
+/// standard library (named directly or through `std` or `alloc`, like [`wrapper`]). Other types with
+/// the same names (like `heapless::Vec<T, N>`) have different methods, so they are left alone
+pub(crate) fn collection(ty: &Type) -> Option<Collection<'_>> {
+    let (ident, args) = self::path_type_args(ty)?;
+    // the collections are also exported from their own modules
+    let is_std = |modules: &[&str]| modules.iter().any(|module| self::is_std_path(ty, module));
+    let ret = match (ident.to_string().as_str(), args.as_slice()) {
+        ("Vec", [elem, ..]) if is_std(&["vec"]) => Collection::Sequence {
+            elem,
+            is_deque: false,
+        },
+        ("VecDeque", [elem, ..]) if is_std(&["collections", "collections::vec_deque"]) => {
+            Collection::Sequence {
+                elem,
+                is_deque: true,
+            }
+        }
+        ("HashMap", [key, value, ..]) if is_std(&["collections", "collections::hash_map"]) => {
+            Collection::Map {
+                key,
+                value,
+                is_ordered: false,
+            }
+        }
+        ("BTreeMap", [key, value, ..]) if is_std(&["collections", "collections::btree_map"]) => {
+            Collection::Map {
+                key,
+                value,
+                is_ordered: true,
+            }
+        }
+        ("HashSet", [elem, ..]) if is_std(&["collections", "collections::hash_set"]) => {
+            Collection::Set {
+                elem,
+                is_ordered: false,
+            }
+        }
+        ("BTreeSet", [elem, ..]) if is_std(&["collections", "collections::btree_set"]) => {
+            Collection::Set {
+                elem,
+                is_ordered: true,
+            }
+        }
+        ("String", []) if is_std(&["string"]) => Collection::String,
+        _ => return None,
+    };
+    Some(ret)
+}
+
+/// The value type of a `core::sync::atomic` type
+pub(crate) struct Atomic {
+    /// The type of the value held by the atomic (for example, `u64` for `AtomicU64`)
+    pub(crate) value: TokenStream,
+    /// True for the integer atomics, which support arithmetic
+    pub(crate) is_integer: bool,
+}
+
+/// Returns the value type, if the type is one of the `core::sync::atomic` types (named directly or
+/// through `core` or `std`, like [`wrapper`])
+pub(crate) fn atomic(ty: &Type) -> Option<Atomic> {
+    if !self::is_std_path(ty, "sync::atomic") {
+        return None;
+    }
+    let (ident, args) = self::path_type_args(ty)?;
+    let (value, is_integer) = match (ident.to_string().as_str(), args.as_slice()) {
+        ("AtomicBool", []) => (quote! { bool }, false),
+        ("AtomicPtr", [pointee]) => (quote! { *mut #pointee }, false),
+        (name, []) => {
+            let integer = name.strip_prefix("Atomic")?.to_lowercase();
+            match integer.as_str() {
+                "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "usize" | "isize" => {
+                    let integer = Ident::new(&integer, ident.span());
+                    (quote! { #integer }, true)
+                }
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(Atomic { value, is_integer })
+}
+
+/// A default memory ordering for atomic accessors, set with `ordering = "..."`
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum AtomicOrdering {
+    Relaxed,
+    Release,
+    Acquire,
+    AcqRel,
+    SeqCst,
+}
+
+impl AtomicOrdering {
+    const NAMES: &'static [&'static str] = &["Relaxed", "Release", "Acquire", "AcqRel", "SeqCst"];
+    /// Parse an `ordering = "..."` item
+    pub(crate) fn parse(lit: &Lit, diag: &mut Diagnostics) -> Option<Self> {
+        let name = match lit {
+            Lit::Str(name) => name.value(),
+            _ => {
+                diag.error(lit, "expected a string literal");
+                return None;
+            }
+        };
+        let ret = match name.as_str() {
+            "Relaxed" => Self::Relaxed,
+            "Release" => Self::Release,
+            "Acquire" => Self::Acquire,
+            "AcqRel" => Self::AcqRel,
+            "SeqCst" => Self::SeqCst,
+            _ => {
+                let message = format!("unknown memory ordering `{}`", name);
+                match self::suggest(&name, Self::NAMES) {
+                    Some(suggestion) => diag.error_with_help(
+                        lit,
+                        message,
+                        format!("did you mean `{}`?", suggestion),
+                    ),
+                    None => diag.error(lit, message),
+                }
+                return None;
+            }
+        };
+        Some(ret)
+    }
+    /// The ordering to use for loads (which cannot have release semantics)
+    pub(crate) fn for_load(self) -> Self {
+        match self {
+            Self::Release => Self::Relaxed,
+            Self::AcqRel => Self::Acquire,
+            o => o,
+        }
+    }
+    /// The ordering to use for stores (which cannot have acquire semantics). `Acquire` is rejected
+    /// by `Stor`, but it is never weakened here either
+    pub(crate) fn for_store(self) -> Self {
+        match self {
+            Self::Acquire => Self::SeqCst,
+            Self::AcqRel => Self::Release,
+            o => o,
+        }
+    }
+}
+
+impl ToTokens for AtomicOrdering {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = match self {
+            Self::Relaxed => quote! { Relaxed },
+            Self::Release => quote! { Release },
+            Self::Acquire => quote! { Acquire },
+            Self::AcqRel => quote! { AcqRel },
+            Self::SeqCst => quote! { SeqCst },
+        };
+        tokens.extend(quote! { ::core::sync::atomic::Ordering::#name });
+    }
+}
+
+/// The lock and cell types that `Gtor` can generate accessors for, along with their contents
+pub(crate) enum Wrapper<'a> {
+    /// `Mutex<T>`
+    Mutex(&'a Type),
+    /// `RwLock<T>`
+    RwLock(&'a Type),
+    /// `Cell<T>`
+    Cell(&'a Type),
+    /// `RefCell<T>`
+    RefCell(&'a Type),
+}
+
+/// Returns the kind of wrapper, if the type is a `Mutex`, `RwLock`, `Cell` or `RefCell` from the
+/// standard library (the accessors name the guard types of `std`, so other locks can't be supported)
+pub(crate) fn wrapper(ty: &Type) -> Option<Wrapper<'_>> {
+    let (ident, args) = self::path_type_args(ty)?;
+    let ret = match (ident.to_string().as_str(), args.as_slice()) {
+        ("Mutex", [inner]) if self::is_std_path(ty, "sync") => Wrapper::Mutex(inner),
+        ("RwLock", [inner]) if self::is_std_path(ty, "sync") => Wrapper::RwLock(inner),
+        ("Cell", [inner]) if self::is_std_path(ty, "cell") => Wrapper::Cell(inner),
+        ("RefCell", [inner]) if self::is_std_path(ty, "cell") => Wrapper::RefCell(inner),
+        _ => return None,
+    };
+    Some(ret)
+}
+
+/// Returns the `#[cfg(...)]` and `#[cfg_attr(...)]` attributes of a field, which have to be mirrored
+/// onto everything that is generated for the field
+pub(crate) fn cfg_attrs(attrs: &[Attribute]) -> TokenStream {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("cfg") || attr.path.is_ident("cfg_attr"))
+        .map(ToTokens::to_token_stream)
+        .collect()
+}
+
+/// Returns `#[allow(deprecated)]` if any of the fields is deprecated, since the generated code that
+/// initializes every field would otherwise warn about using them
+pub(crate) fn allow_deprecated<'a>(
+    mut attrs: impl Iterator<Item = &'a Vec<Attribute>>,
+) -> TokenStream {
+    if attrs.any(|attrs| attrs.iter().any(|attr| attr.path.is_ident("deprecated"))) {
+        quote! { #[allow(deprecated)] }
+    } else {
+        quote! {}
+    }
+}
+
+/// A doc comment template for generated methods, set with `doc = "..."` on the container. The
+/// `{field}`, `{struct}` and `{method}` placeholders are replaced with the names of the field, the
+/// struct and the generated method
+pub(crate) struct DocTemplate(String);
+
+impl DocTemplate {
+    const PLACEHOLDERS: &'static [&'static str] = &["field", "struct", "method"];
+    /// Parse a `doc = "..."` item, making sure that every placeholder is known
+    pub(crate) fn parse(lit: &Lit, diag: &mut Diagnostics) -> Option<Self> {
+        let template = match lit {
+            Lit::Str(template) => template.value(),
+            _ => {
+                diag.error(lit, "expected a string literal");
+                return None;
+            }
+        };
+        let mut is_valid = true;
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find('{') {
+            let after = &rest[start + 1..];
+            let end = match after.find('}') {
+                Some(end) => end,
+                None => {
+                    diag.error(lit, "unclosed `{` in doc template");
+                    return None;
+                }
+            };
+            let name = &after[..end];
+            if !Self::PLACEHOLDERS.contains(&name) {
+                let message = format!("unknown placeholder `{{{}}}` in doc template", name);
+                match self::suggest(name, Self::PLACEHOLDERS) {
+                    Some(suggestion) => diag.error_with_help(
+                        lit,
+                        message,
+                        format!("did you mean `{{{}}}`?", suggestion),
+                    ),
+                    None => diag.error(lit, message),
+                }
+                is_valid = false;
+            }
+            rest = &after[end + 1..];
+        }
+        if is_valid {
+            Some(Self(template))
+        } else {
+            None
+        }
+    }
+    fn render(&self, field: &Ident, struct_name: &Ident, method: &Ident) -> String {
+        self.0
+            .replace("{field}", &field.to_string())
+            .replace("{struct}", &struct_name.to_string())
+            .replace("{method}", &method.to_string())
+    }
+}
+
+/// The outer attributes added to every generated method
+pub(crate) enum MethodAttrs {
+    /// The attributes given with `attrs(...)`, which replace the defaults
+    Explicit(TokenStream),
+    /// `#[inline]` on every method, and (if `must_use` is set) `#[must_use]` on every method that
+    /// returns a value
+    Defaults { must_use: bool },
+}
+
+impl MethodAttrs {
+    /// Parses the item if it is an `attrs(...)` list, returning `None` if it isn't
+    pub(crate) fn parse(item: &NestedMeta, diag: &mut Diagnostics) -> Option<Self> {
+        let list = match item {
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("attrs") => list,
+            _ => return None,
+        };
+        let mut attrs = quote! {};
+        for nested in list.nested.iter() {
+            match nested {
+                NestedMeta::Meta(meta) => {
+                    attrs = quote! {
+                        #attrs
+                        #[#meta]
+                    }
+                }
+                NestedMeta::Lit(lit) => diag.error(lit, "expected an attribute, like `inline`"),
+            }
+        }
+        Some(Self::Explicit(attrs))
+    }
+    /// Returns the attributes for the given method
+    fn for_method(&self, method: &Method) -> TokenStream {
+        match self {
+            Self::Explicit(attrs) => attrs.clone(),
+            Self::Defaults { must_use } => {
+                if *must_use && method.returns_value {
+                    quote! { #[inline] #[must_use] }
+                } else {
+                    quote! { #[inline] }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the doc comments (`#[doc = "..."]`) of a field
+pub(crate) fn field_docs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
         .iter()
         .filter(|attr| {
-            span = Some((*attr).span());
-            attr.path.is_ident(target)
+            matches!(
+                attr.parse_meta(),
+                Ok(Meta::NameValue(nv)) if nv.path.is_ident("doc") && matches!(nv.lit, Lit::Str(_))
+            )
         })
-        .count();
-    match has_attr {
-        0 => Ok(false),
-        1 => Ok(true),
-        _ => Err(syn::Error::new(
-            span.unwrap(),
-            format!("Found duplicate attributes for `{}`", target),
+        .collect()
+}
+
+/// A method generated for a field, without its attributes (which are added by [`finish_methods`])
+pub(crate) struct Method {
+    name: Ident,
+    doc: String,
+    /// Whether the method returns a value, which decides if it gets `#[must_use]`
+    returns_value: bool,
+    /// The signature and the body of the method
+    item: TokenStream,
+}
+
+impl Method {
+    /// A method that returns a value
+    pub(crate) fn returning(name: &Ident, doc: String, item: TokenStream) -> Self {
+        Self {
+            name: name.clone(),
+            doc,
+            returns_value: true,
+            item,
+        }
+    }
+    /// A method that doesn't return a value
+    pub(crate) fn unit(name: &Ident, doc: String, item: TokenStream) -> Self {
+        Self {
+            name: name.clone(),
+            doc,
+            returns_value: false,
+            item,
+        }
+    }
+}
+
+/// Finishes the methods generated for a field:
+/// - with a doc `template`, the generated doc comments are replaced with the rendered template
+/// - the field's own doc comments are appended to the doc comments
+/// - the `cfg` attributes (see [`cfg_attrs`]), `#[deprecated]` and `#[doc(hidden)]` of the field are
+///   carried over
+/// - the `method_attrs` are added
+pub(crate) fn finish_methods(
+    methods: Vec<Method>,
+    field: &Ident,
+    attrs: &[Attribute],
+    template: Option<&DocTemplate>,
+    method_attrs: &MethodAttrs,
+    struct_name: &Ident,
+) -> TokenStream {
+    let is_doc_hidden = |attr: &Attribute| match attr.parse_meta() {
+        Ok(Meta::List(list)) if list.path.is_ident("doc") => list.nested.iter().any(
+            |item| matches!(item, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("hidden")),
+        ),
+        _ => false,
+    };
+    let carried: Vec<&Attribute> = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("deprecated") || is_doc_hidden(attr))
+        .collect();
+    let cfgs = self::cfg_attrs(attrs);
+    let docs = self::field_docs(attrs);
+    // separate the field's docs from the generated summary with an empty line
+    let docs = if docs.is_empty() {
+        quote! {}
+    } else {
+        quote! { #[doc = ""] #(#docs)* }
+    };
+    methods
+        .iter()
+        .map(|method| {
+            let summary = match template {
+                Some(template) => template.render(field, struct_name, &method.name),
+                None => method.doc.clone(),
+            };
+            let added = method_attrs.for_method(method);
+            let item = &method.item;
+            quote! {
+                #[doc = #summary]
+                #docs
+                #cfgs
+                #(#carried)*
+                #added
+                #item
+            }
+        })
+        .collect()
+}
+
+/// Returns true if the struct is `#[repr(packed)]` (or `#[repr(packed(N))]`)
+pub(crate) fn is_packed(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("repr"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|item| match item {
+            NestedMeta::Meta(meta) => meta.path().is_ident("packed"),
+            NestedMeta::Lit(_) => false,
+        })
+}
+
+/// Returns true if the type mentions one of the type parameters in `generics`. A bound on such a
+/// type is only checked where the method that has it is used, while a bound on any other type is
+/// checked with the struct (and is an error if it doesn't hold)
+pub(crate) fn mentions_type_param(ty: &Type, generics: &Generics) -> bool {
+    fn mentions(tokens: TokenStream, params: &[&Ident]) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(ident) => params.contains(&&ident),
+            TokenTree::Group(group) => mentions(group.stream(), params),
+            _ => false,
+        })
+    }
+    let params: Vec<&Ident> = generics.type_params().map(|param| &param.ident).collect();
+    !params.is_empty() && mentions(ty.to_token_stream(), &params)
+}
+
+/// Returns the bound that requires a field of a packed struct to be `Copy`, so that it can be read
+/// and written without taking references to it, along with a check for the start of the method
+/// body. A `Copy` bound on a type that doesn't mention a type parameter would be a hard error
+/// without a hint, so such fields are checked in the body instead (spanned at the field's type so
+/// that the error points at the offending field, and so that the body doesn't report more errors)
+pub(crate) fn packed_check(ty: &Type, generics: &Generics) -> (TokenStream, TokenStream) {
+    if self::mentions_type_param(ty, generics) {
+        (quote! { #ty: ::core::marker::Copy }, quote! {})
+    } else {
+        (
+            quote! {},
+            quote_spanned! {ty.span()=> ::bagel::__private::check_packed_field::<#ty>(); },
         )
-        .into_compile_error()
-        .into()),
     }
 }
 
@@ -67,8 +800,9 @@ pub(crate) fn single_instance_of_attr(
 pub(crate) fn get_func_header(
     attrs: &[Attribute],
     target: &str,
-) -> Result<quote::__private::TokenStream, TokenStream> {
-    let r = if self::single_instance_of_attr(attrs, target)? {
+    diag: &mut Diagnostics,
+) -> TokenStream {
+    if self::single_instance_of_attr(attrs, target, diag) {
         quote! {
             pub const fn
         }
@@ -76,6 +810,5 @@ pub(crate) fn get_func_header(
         quote! {
             pub fn
         }
-    };
-    Ok(r)
+    }
 }
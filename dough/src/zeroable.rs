@@ -0,0 +1,67 @@
+//! This module is responsible for the generation of the `Zeroable` marker impl
+//!
+
+use ::proc_macro::TokenStream;
+use ::quote::{quote, quote_spanned};
+use ::syn::spanned::Spanned;
+use ::syn::{parse_macro_input, parse_quote, Data, DeriveInput, Type};
+
+pub(crate) fn derive_zeroable(input: TokenStream) -> TokenStream {
+    let ast: DeriveInput = parse_macro_input!(input);
+    expand(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(ast: &DeriveInput) -> syn::Result<::proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let types = self::field_types(ast)?;
+    // the impl only holds if the type parameters are zeroable
+    let mut generics = ast.generics.clone();
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause
+            .predicates
+            .push(parse_quote! { #param: ::bagel::Zeroable });
+    }
+    let (impl_gen, ty_gen, where_clause) = generics.split_for_impl();
+    let asserts = self::assert_zeroable(&types);
+    Ok(quote! {
+        unsafe impl #impl_gen ::bagel::Zeroable for #name #ty_gen #where_clause {}
+        const _: () = {
+            #[allow(dead_code)]
+            fn assert_fields #impl_gen () #where_clause {
+                #asserts
+            }
+        };
+    })
+}
+
+/// Returns the types of every field of a struct or union
+pub(crate) fn field_types(ast: &DeriveInput) -> syn::Result<Vec<&Type>> {
+    match &ast.data {
+        Data::Struct(data) => Ok(data.fields.iter().map(|field| &field.ty).collect()),
+        Data::Union(data) => Ok(data.fields.named.iter().map(|field| &field.ty).collect()),
+        Data::Enum(_) => Err(syn::Error::new_spanned(
+            ast,
+            "this macro can only be used on structs and unions",
+        )),
+    }
+}
+
+/// Returns statements that fail to compile (pointing at the type) if any of the types is not
+/// zeroable
+pub(crate) fn assert_zeroable(types: &[&Type]) -> ::proc_macro2::TokenStream {
+    types
+        .iter()
+        .map(|ty| {
+            quote_spanned! {ty.span()=>
+                ::bagel::__private::assert_zeroable::<#ty>();
+            }
+        })
+        .collect()
+}